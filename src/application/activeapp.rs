@@ -1,11 +1,22 @@
-use egui::{CentralPanel, Color32, FontId, Rect, SidePanel, TopBottomPanel, Ui};
+use egui::{
+    vec2, Button, CentralPanel, CollapsingHeader, Color32, ComboBox, FontId, Rect, ScrollArea,
+    Sense, SidePanel, Stroke, StrokeKind, TopBottomPanel, Ui,
+};
 
-use crate::error::{applicationerror::ApplicationError, babaerror::BabaError};
-use std::fmt::Debug;
+use crate::{
+    error::babaerror::BabaError, files::babafiles::BabaFiles, merge::mergeoptions::MergeOptions,
+    mods::babamod::BabaMod,
+};
+use std::{fmt::Debug, path::PathBuf};
 
 use super::{
-    appoptions::AppOptions, appstate::AppState, load_fonts, load_themes, status::Status,
+    appoptions::AppOptions,
+    appstate::{AppState, ListedPack},
+    matches_filter,
+    packsortorder::{compare_packs, PackSortOrder},
+    status::Status,
     themedata::ThemeData,
+    SWATCH_SIZE,
 };
 
 /// A quick way to create an [egui::Image] via an invocation of [egui::include_image].
@@ -20,6 +31,9 @@ macro_rules! image {
 
 /// Represents the currently running application.
 /// Has access to all the data involved by mutable reference.
+///
+/// A fresh one is made by [`Status::render`] every frame, and only lives for that frame,
+/// so anything expensive belongs in [`super::app::App::setup`] instead.
 pub struct ActiveApp<'a> {
     /// The currently running context.
     /// Used for hooking into [`egui`] and general GUI-related functions.
@@ -34,6 +48,9 @@ pub struct ActiveApp<'a> {
 
 impl<'a> ActiveApp<'a> {
     /// Creates a new "active" application from its baseline data.
+    ///
+    /// This is done every frame, so it is cheap and does no IO;
+    /// loading palettes and fonts happens once, in [`super::app::App::new`].
     pub fn new(
         ctx: &'a egui::Context,
         frame: &'a mut eframe::Frame,
@@ -41,19 +58,19 @@ impl<'a> ActiveApp<'a> {
         status: &'a mut Status,
         options: &'a mut AppOptions,
     ) -> Self {
-        let mut this = Self {
+        Self {
             ctx,
             frame,
             state,
             status,
             options,
-        };
-        let _ = this.setup();
-        this
+        }
     }
 
     /// This is the main function called whenever updates need to be run.
     pub fn render(&mut self) -> Result<(), BabaError> {
+        // panels have to be added before the central panel
+        self.error_bar();
         match self.status {
             Status::Startup => self.startup(),
             Status::Settings => self.settings(),
@@ -62,20 +79,75 @@ impl<'a> ActiveApp<'a> {
         }
     }
 
-    fn setup(&mut self) -> Result<(), BabaError> {
-        // application setup: load palettes
-        let palettes = load_themes()?;
-        self.state.palettes = palettes;
-        // load font
-        self.load_currently_selected_font()?;
-        Ok(())
+    /// Shows the most recent error (if any) in a bar along the bottom of the window.
+    fn error_bar(&mut self) {
+        let Some(error) = self.state.last_error.clone() else {
+            return;
+        };
+        let ctx = self.ctx;
+        let color = self.error_color();
+        let mut dismissed = false;
+        bottom_panel("error_bar").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                dismissed = ui.button("Dismiss").clicked();
+                ui.colored_label(color, error);
+            });
+        });
+        if dismissed {
+            self.state.dismiss_error();
+        }
+    }
+
+    /// Pushes the colors of the current theme into egui, see [`AppOptions::apply_theme`].
+    pub fn apply_theme(&self) {
+        self.options.apply_theme(self.ctx);
     }
 
+    /// The first screen, which finds the installation of Baba is You before moving on to the overview.
+    ///
+    /// An installation remembered from the last run (or found by [`super::app::App::setup`]) is used
+    /// straight away, otherwise the user is asked where it is.
     pub fn startup(&mut self) -> Result<(), BabaError> {
-        Ok(())
+        if self.state.has_files() {
+            *self.status = Status::Overview;
+            return Ok(());
+        }
+        self.installation_prompt()
     }
 
     pub fn settings(&mut self) -> Result<(), BabaError> {
+        let ctx = self.ctx;
+        let mut chosen_font = self.options.font.clone();
+        let current_theme = self.options.theme.colors();
+        let mut chosen_theme = None;
+        central_panel().show(ctx, |ui| {
+            ui.heading("Settings");
+            ComboBox::from_label("Font")
+                .selected_text(chosen_font.clone())
+                .show_ui(ui, |ui| {
+                    for font in &self.state.fonts {
+                        ui.selectable_value(&mut chosen_font, font.name.clone(), &font.name);
+                    }
+                });
+            ui.separator();
+            ui.label("Theme");
+            if self.state.palettes.is_empty() {
+                ui.label("No palettes could be found.");
+            }
+            for theme in &self.state.palettes {
+                if theme_swatch(ui, theme, theme.colors() == current_theme) {
+                    chosen_theme = Some(*theme);
+                }
+            }
+        });
+        if let Some(theme) = chosen_theme {
+            self.options.theme = theme;
+            self.apply_theme();
+        }
+        if chosen_font != self.options.font {
+            self.options.font = chosen_font;
+            self.load_currently_selected_font();
+        }
         Ok(())
     }
 
@@ -83,26 +155,221 @@ impl<'a> ActiveApp<'a> {
         Ok(())
     }
 
+    /// Lists every levelpack, and the mods inside of them.
+    /// Selecting two mods allows them to be merged.
+    ///
+    /// Only the packs matching the filter are listed (see [`matches_filter`]),
+    /// in the chosen sort order (see [`compare_packs`]).
+    ///
+    /// Mods (`.zip` archives, folders, or `.lua` files) dropped onto the window
+    /// are offered to be installed into one of the packs.
+    ///
+    /// The packs are kept in the state (see [`AppState::refresh_packs`]), and are only read again
+    /// when asked to, or after files are dropped, a mod is installed, or mods are merged.
     pub fn overview(&mut self) -> Result<(), BabaError> {
+        let ctx = self.ctx;
+        // without an installation, there's nothing to list
+        if !self.state.has_files() {
+            *self.status = Status::Startup;
+            return Ok(());
+        }
+        let dropped = ctx.input(|input| input.raw.dropped_files.clone());
+        if self.state.packs.is_none() || !dropped.is_empty() {
+            self.state.refresh_packs()?;
+        }
+        self.state.receive_dropped_files(&dropped);
+        // taken out of the state while listing, so the state can still be borrowed mutably
+        let mut packs = self.state.packs.take().unwrap_or_default();
+        let installed = match self.state.dropped_mods.is_empty() {
+            true => Ok(false),
+            false => self.install_prompt(&packs),
+        };
+        let sort_order = self.options.sort_order;
+        packs.sort_by(|left, right| compare_packs(&left.pack, &right.pack, sort_order));
+        let selected = self.state.selected_mods.clone();
+        let mut toggled = Vec::new();
+        let mut merge = false;
+        let mut refresh = false;
+        central_panel().show(ctx, |ui| {
+            ui.heading("Levelpacks");
+            ui.horizontal(|ui| {
+                merge = ui
+                    .add_enabled(selected.len() == 2, Button::new("Merge selected mods"))
+                    .clicked();
+                refresh = ui.button("Refresh").clicked();
+            });
+            ui.horizontal(|ui| {
+                ui.label("Filter:");
+                ui.text_edit_singleline(&mut self.state.filter);
+                ComboBox::from_label("Sort by")
+                    .selected_text(self.options.sort_order.to_string())
+                    .show_ui(ui, |ui| {
+                        for order in PackSortOrder::ALL {
+                            ui.selectable_value(
+                                &mut self.options.sort_order,
+                                order,
+                                order.to_string(),
+                            );
+                        }
+                    });
+            });
+            ui.separator();
+            let filter = &self.state.filter;
+            ScrollArea::vertical().show(ui, |ui| {
+                // packs whose mods couldn't be loaded are only matched by name and author
+                let matching = packs.iter().filter(|listed| {
+                    let mods = listed.mods.as_deref().unwrap_or_default();
+                    matches_filter(&listed.pack, mods, filter)
+                });
+                for listed in matching {
+                    levelpack_entry(ui, listed, &selected, &mut toggled);
+                }
+            });
+        });
+        self.state.packs = Some(packs);
+        for path in toggled {
+            self.state.toggle_selected_mod(path);
+        }
+        if merge {
+            self.merge_selected_mods()?;
+        }
+        if refresh || merge || installed? {
+            self.state.refresh_packs()?;
+        }
         Ok(())
     }
 
-    pub fn load_currently_selected_font(&self) -> Result<(), BabaError> {
-        for font in load_fonts()? {
-            if font.name == self.options.font {
-                self.ctx.add_font(font);
-            }
+    /// Asks the user where their installation of Baba is You is, either by looking
+    /// in the usual folders again (see [`BabaFiles::detect`]) or by entering its path.
+    ///
+    /// Once an installation is found, the overview is shown.
+    fn installation_prompt(&mut self) -> Result<(), BabaError> {
+        let ctx = self.ctx;
+        let mut detect = false;
+        let mut submitted = false;
+        central_panel().show(ctx, |ui| {
+            ui.heading("Where is Baba is You installed?");
+            ui.label("It could not be found in any of the usual folders.");
+            detect = ui.button("Look again").clicked();
+            ui.separator();
+            ui.label("Please enter the path to the folder Baba is You is installed in:");
+            ui.text_edit_singleline(&mut self.state.path_input);
+            submitted = ui.button("Use this folder").clicked();
+        });
+        let files = match (detect, submitted) {
+            (true, _) => BabaFiles::detect()?,
+            (_, true) => BabaFiles::from_raw_checked(PathBuf::from(&self.state.path_input))?,
+            _ => return Ok(()),
+        };
+        self.state.set_files(files);
+        *self.status = Status::Overview;
+        Ok(())
+    }
+
+    /// Asks which levelpack the first dropped mod should be installed into,
+    /// listing any installed mods it conflicts with (see [`BabaMod::is_compatible_with`]).
+    ///
+    /// Returns whether the mod was installed.
+    fn install_prompt(&mut self, packs: &[ListedPack]) -> Result<bool, BabaError> {
+        let ctx = self.ctx;
+        // taken out of the state while prompting, so the state can still be borrowed mutably
+        let mut dropped = std::mem::take(&mut self.state.dropped_mods);
+        let Some(baba_mod) = dropped.first() else {
+            return Ok(false);
+        };
+        let mut prompt = std::mem::take(&mut self.state.install_prompt);
+        let mut target = prompt.target.clone();
+        let listed = packs
+            .iter()
+            .find(|listed| target.as_ref() == Some(listed.pack.path()));
+        let conflicts = prompt.conflicts.get_or_insert_with(|| match listed {
+            Some(listed) => listed.conflicts_with(baba_mod),
+            None => Vec::new(),
+        });
+        let pack = listed.map(|listed| &listed.pack);
+        let mut install = false;
+        let mut cancel = false;
+        egui::Window::new(format!("Install {}", baba_mod.name()))
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ComboBox::from_label("Levelpack")
+                    .selected_text(pack.map(|pack| pack.name()).unwrap_or_default())
+                    .show_ui(ui, |ui| {
+                        for ListedPack { pack, .. } in packs {
+                            ui.selectable_value(
+                                &mut target,
+                                Some(pack.path().to_owned()),
+                                pack.name(),
+                            );
+                        }
+                    });
+                match (pack, conflicts.is_empty()) {
+                    (None, _) => ui.label("Pick a levelpack to install into."),
+                    (Some(_), true) => ui.label("No conflicts with the installed mods."),
+                    (Some(_), false) => ui.colored_label(
+                        self.error_color(),
+                        format!("Conflicts with: {}", conflicts.join(", ")),
+                    ),
+                };
+                ui.horizontal(|ui| {
+                    install = ui
+                        .add_enabled(
+                            pack.is_some() && conflicts.is_empty(),
+                            Button::new("Install"),
+                        )
+                        .clicked();
+                    cancel = ui.button("Cancel").clicked();
+                });
+            });
+        let result = match (install, pack, self.state.files()) {
+            (true, Some(pack), Some(files)) => pack.install_mod(baba_mod, files).map(|_| true),
+            _ => Ok(false),
+        };
+        // the conflicts are found again for the next mod, or the newly picked levelpack
+        if install || cancel {
+            dropped.remove(0);
+            prompt.conflicts = None;
+        }
+        if target != prompt.target {
+            prompt.target = target;
+            prompt.conflicts = None;
         }
+        self.state.dropped_mods = dropped;
+        self.state.install_prompt = prompt;
+        result
+    }
+
+    /// Merges the two selected mods, placing the result next to the first of the two.
+    fn merge_selected_mods(&mut self) -> Result<(), BabaError> {
+        let [left, right] = self.state.selected_mods.as_slice() else {
+            return Ok(());
+        };
+        let left = BabaMod::new(left.clone());
+        let right = BabaMod::new(right.clone());
+        let options = MergeOptions {
+            location: left
+                .path()
+                .parent()
+                .map(ToOwned::to_owned)
+                .unwrap_or_default(),
+            ..Default::default()
+        };
+        if let Some(files) = self.state.files() {
+            left.merge_with(&right, files, options)?;
+        }
+        self.state.selected_mods.clear();
         Ok(())
     }
 
+    /// Adds the selected font to egui, see [`AppOptions::load_selected_font`].
+    pub fn load_currently_selected_font(&mut self) {
+        self.options.load_selected_font(self.ctx, &self.state.fonts);
+    }
+
+    /// Gets the selected font at the given size, see [`AppOptions::selected_font_with_size`].
     pub fn currently_selected_font_with_size(&self, size: f32) -> Result<FontId, BabaError> {
-        for font in load_fonts()? {
-            if font.name == self.options.font {
-                return Ok(FontId::new(size, egui::FontFamily::Name(font.name.into())));
-            }
-        }
-        Err(BabaError::Application(ApplicationError::FontUnavailible))
+        self.options
+            .selected_font_with_size(size, &self.state.fonts)
     }
 
     pub fn install_image_loaders(&self) {
@@ -210,5 +477,67 @@ fn central_panel() -> CentralPanel {
     CentralPanel::default()
 }
 
+/// Shows a single levelpack in the overview, alongside its mods.
+///
+/// Any mods whose checkbox was clicked are pushed onto `toggled`.
+fn levelpack_entry(
+    ui: &mut Ui,
+    listed: &ListedPack,
+    selected: &[PathBuf],
+    toggled: &mut Vec<PathBuf>,
+) {
+    let pack = &listed.pack;
+    CollapsingHeader::new(format!("{} by {}", pack.name(), pack.author()))
+        .id_salt(pack.path())
+        .show(ui, |ui| {
+            let mods = match &listed.mods {
+                Ok(mods) => mods,
+                Err(error) => {
+                    ui.label(format!("Could not load the mods for this pack:\n{}", error));
+                    return;
+                }
+            };
+            if mods.is_empty() {
+                ui.label("This pack has no mods.");
+            }
+            for baba_mod in mods {
+                let path = baba_mod.path().to_owned();
+                let mut checked = selected.contains(&path);
+                if ui.checkbox(&mut checked, baba_mod.name()).changed() {
+                    toggled.push(path);
+                }
+                let authors = baba_mod.authors();
+                if !authors.is_empty() {
+                    ui.label(format!("By {}", authors.join(", ")));
+                }
+                ui.label(baba_mod.description());
+                ui.separator();
+            }
+        });
+}
+
+/// Shows a theme as a strip of its colors, outlined if it is the selected theme.
+///
+/// Returns whether or not the strip was clicked.
+fn theme_swatch(ui: &mut Ui, theme: &ThemeData, selected: bool) -> bool {
+    let colors = theme.colors();
+    let size = vec2(SWATCH_SIZE * colors.len() as f32, SWATCH_SIZE);
+    let (rect, response) = ui.allocate_exact_size(size, Sense::click());
+    let painter = ui.painter();
+    for (i, color) in colors.into_iter().enumerate() {
+        let min = rect.min + vec2(SWATCH_SIZE * i as f32, 0.0);
+        painter.rect_filled(
+            Rect::from_min_size(min, vec2(SWATCH_SIZE, SWATCH_SIZE)),
+            0.0,
+            color,
+        );
+    }
+    if selected {
+        let stroke = Stroke::new(2.0, ui.visuals().selection.stroke.color);
+        painter.rect_stroke(rect, 0.0, stroke, StrokeKind::Outside);
+    }
+    response.clicked()
+}
+
 /// Explitily do nothing with a [Ui] object.
 fn do_nothing(_ui: &mut Ui) {}
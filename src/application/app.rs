@@ -1,11 +1,22 @@
+use std::path::PathBuf;
+
 use serde::{Deserialize, Serialize};
 
-use super::{appoptions::AppOptions, appstate::AppState, status::Status};
+use crate::{error::babaerror::BabaError, files::babafiles::BabaFiles};
+
+use super::{
+    appoptions::AppOptions, appstate::AppState, bundled_fonts, bundled_theme, load_fonts_from,
+    load_themes_from, status::Status, FONTS_DIR, OPTIONS_KEY, PALETTES_DIR, STATE_KEY,
+};
 
 #[derive(Default, Debug, Serialize, Deserialize)]
 /// A general template to hold Application-related data.
 /// All working logic is stored within the implementation details for
 /// ActiveApp, however.
+///
+/// Anything expensive (reading palettes and fonts from disk) is done once, by [`App::setup`]
+/// when the application is created. After that, every frame [`Status::render`] lays out the UI
+/// through a short-lived [`super::activeapp::ActiveApp`].
 pub struct App {
     state: AppState,
     status: Status,
@@ -14,17 +25,65 @@ pub struct App {
 
 impl eframe::App for App {
     fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
-        match self.status
-            .render(ctx, frame, &mut self.state, &mut self.options) {
-                Ok(_) => {}, // explicitly do nothing if everything went alright when updating
-                Err(e) => todo!("Should log errors somewhere instead of crashing. Error: {}", e),
-            }
+        match self
+            .status
+            .render(ctx, frame, &mut self.state, &mut self.options)
+        {
+            Ok(_) => {} // explicitly do nothing if everything went alright when updating
+            Err(e) => self.state.push_error(e), // shown in the error bar on the next frame
+        }
+    }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, OPTIONS_KEY, &self.options);
+        eframe::set_value(storage, STATE_KEY, &self.state);
     }
 }
 
 impl App {
     /// Creates a new Application with a [eframe::CreationContext].
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
-        Self::default()
+    ///
+    /// Options and state saved by a previous run are restored from the context's storage, if any.
+    /// Fonts and palettes are loaded from `data_dir` (see [`super::find_data_dir`]),
+    /// or only the bundled ones are used if there is none.
+    pub fn new(cc: &eframe::CreationContext<'_>, data_dir: Option<PathBuf>) -> Self {
+        let mut this = match cc.storage {
+            Some(storage) => Self {
+                options: eframe::get_value(storage, OPTIONS_KEY).unwrap_or_default(),
+                state: eframe::get_value(storage, STATE_KEY).unwrap_or_default(),
+                ..Default::default()
+            },
+            None => Self::default(),
+        };
+        this.state.data_dir = data_dir;
+        if let Err(e) = this.setup(&cc.egui_ctx) {
+            this.state.push_error(e);
+        }
+        this
+    }
+
+    /// Does the one-time work needed before the first frame:
+    /// looks for the installation of Baba is You (unless one was remembered), installs image loaders,
+    /// loads the palettes, and applies the selected font and theme.
+    pub fn setup(&mut self, ctx: &egui::Context) -> Result<(), BabaError> {
+        // only ask the user where the game is if it can't be found on its own
+        if !self.state.has_files() {
+            if let Ok(files) = BabaFiles::detect() {
+                self.state.set_files(files);
+            }
+        }
+        egui_extras::install_image_loaders(ctx);
+        let data_dir = self.state.data_dir.clone();
+        self.state.ensure_palettes(|| match &data_dir {
+            Some(dir) => load_themes_from(&dir.join(PALETTES_DIR)),
+            None => Ok(vec![bundled_theme()?]),
+        })?;
+        self.state.fonts = match &data_dir {
+            Some(dir) => load_fonts_from(&dir.join(FONTS_DIR))?,
+            None => bundled_fonts(),
+        };
+        self.options.load_selected_font(ctx, &self.state.fonts);
+        self.options.apply_theme(ctx);
+        Ok(())
     }
 }
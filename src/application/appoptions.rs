@@ -1,10 +1,97 @@
+use egui::{
+    epaint::text::{FontInsert, FontPriority, InsertFontFamily},
+    FontFamily, FontId, Visuals,
+};
 use serde::{Deserialize, Serialize};
 
-use super::themedata::ThemeData;
+use crate::error::{applicationerror::ApplicationError, babaerror::BabaError};
+
+use super::{packsortorder::PackSortOrder, themedata::ThemeData, DEFAULT_FONT};
 
 #[derive(Default, Debug, Serialize, Deserialize)]
 pub struct AppOptions {
     pub theme: ThemeData,
     pub light_mode: bool,
     pub font: String,
+    /// The order levelpacks are listed in on the overview
+    #[serde(default)]
+    pub sort_order: PackSortOrder,
+}
+
+impl AppOptions {
+    /// Pushes the colors of the theme into egui's [Visuals].
+    ///
+    /// Whether the background is light or dark is decided by [AppOptions::light_mode].
+    pub fn apply_theme(&self, ctx: &egui::Context) {
+        let theme = &self.theme;
+        let (mut visuals, background, accent, text) = match self.light_mode {
+            true => (
+                Visuals::light(),
+                theme.light.color(),
+                theme.light_accent.color(),
+                theme.dark.color(),
+            ),
+            false => (
+                Visuals::dark(),
+                theme.dark.color(),
+                theme.dark_accent.color(),
+                theme.light.color(),
+            ),
+        };
+        visuals.panel_fill = background;
+        visuals.window_fill = background;
+        visuals.extreme_bg_color = accent;
+        visuals.faint_bg_color = accent;
+        visuals.hyperlink_color = theme.link.color();
+        visuals.warn_fg_color = theme.warning.color();
+        visuals.error_fg_color = theme.error.color();
+        visuals.override_text_color = Some(text);
+        ctx.set_visuals(visuals);
+    }
+
+    /// Adds the selected font to egui, as the primary font for regular text,
+    /// with [DEFAULT_FONT] as a fallback for any glyphs it's missing.
+    ///
+    /// The selected font is looked up in `fonts` (see [`super::load_fonts_from`]).
+    /// If it can no longer be found, [DEFAULT_FONT] is selected instead.
+    pub fn load_selected_font(&mut self, ctx: &egui::Context, fonts: &[FontInsert]) {
+        if !fonts.iter().any(|font| font.name == self.font) {
+            self.font = DEFAULT_FONT.to_owned();
+        }
+        for font in fonts {
+            let mut font = font.clone();
+            if font.name == self.font {
+                // the selected font comes first for all regular text
+                for family in &mut font.families {
+                    family.priority = FontPriority::Highest;
+                }
+                ctx.add_font(font);
+            } else if font.name == DEFAULT_FONT {
+                // and the default font fills in any glyphs it's missing
+                font.families.push(InsertFontFamily {
+                    family: FontFamily::Name(self.font.as_str().into()),
+                    priority: FontPriority::Lowest,
+                });
+                ctx.add_font(font);
+            }
+        }
+    }
+
+    /// Gets the selected font at the given size.
+    ///
+    /// # Errors
+    /// Returns [`ApplicationError::FontUnavailable`] if the selected font isn't one of `fonts`.
+    pub fn selected_font_with_size(
+        &self,
+        size: f32,
+        fonts: &[FontInsert],
+    ) -> Result<FontId, BabaError> {
+        if !fonts.iter().any(|font| font.name == self.font) {
+            return Err(ApplicationError::FontUnavailable(self.font.clone()).into());
+        }
+        Ok(FontId::new(
+            size,
+            FontFamily::Name(self.font.as_str().into()),
+        ))
+    }
 }
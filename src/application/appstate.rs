@@ -1,13 +1,89 @@
+use std::{fmt::Display, path::PathBuf};
+
+use egui::{epaint::text::FontInsert, DroppedFile};
 use serde::{Deserialize, Serialize};
 
-use crate::files::babafiles::BabaFiles;
+use crate::{
+    error::babaerror::BabaError, files::babafiles::BabaFiles,
+    levelpack::levelpackrepr::LevelpackRepr, mods::babamod::BabaMod,
+};
 
-use super::themedata::ThemeData;
+use super::{mod_from_dropped_file, themedata::ThemeData};
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct AppState {
+    /// The folder fonts and palettes are loaded from, see [`super::find_data_dir`]
+    #[serde(skip)]
+    pub data_dir: Option<PathBuf>,
+    /// Reloaded from disk on startup, so not saved between runs
+    #[serde(skip)]
     pub palettes: Vec<ThemeData>,
-    files: Option<BabaFiles>
+    /// The fonts that can be selected, loaded once on startup rather than every frame
+    #[serde(skip)]
+    pub fonts: Vec<FontInsert>,
+    files: Option<BabaFiles>,
+    /// The path typed in by the user, when no installation could be found automatically
+    #[serde(skip)]
+    pub path_input: String,
+    /// The text typed into the overview's filter, see [`super::matches_filter`]
+    #[serde(skip)]
+    pub filter: String,
+    /// The mods (by path) selected in the overview
+    #[serde(skip)]
+    pub selected_mods: Vec<PathBuf>,
+    /// The most recent error, shown until dismissed
+    #[serde(skip)]
+    pub last_error: Option<String>,
+    /// Mods dropped onto the overview, waiting to be installed (the first one is being prompted for)
+    #[serde(skip)]
+    pub dropped_mods: Vec<BabaMod>,
+    /// What has been picked so far in the prompt to install the first of the dropped mods
+    #[serde(skip)]
+    pub install_prompt: InstallPrompt,
+    /// The levelpacks listed on the overview, only read from disk by [`AppState::refresh_packs`]
+    #[serde(skip)]
+    pub packs: Option<Vec<ListedPack>>,
+}
+
+/// A levelpack listed on the overview, alongside its mods.
+#[derive(Debug)]
+pub struct ListedPack {
+    pub pack: LevelpackRepr,
+    /// The mods of the pack, or why they couldn't be loaded
+    pub mods: Result<Vec<BabaMod>, BabaError>,
+}
+
+impl ListedPack {
+    /// The names of the mods in this pack that `baba_mod` can't be installed alongside
+    /// (see [`BabaMod::is_compatible_with`]). If the mods couldn't be loaded, none are given.
+    pub fn conflicts_with(&self, baba_mod: &BabaMod) -> Vec<String> {
+        let Ok(mods) = &self.mods else {
+            return Vec::new();
+        };
+        mods.iter()
+            .filter(|installed| !baba_mod.is_compatible_with(installed))
+            .map(|installed| installed.name())
+            .collect()
+    }
+}
+
+impl From<LevelpackRepr> for ListedPack {
+    fn from(pack: LevelpackRepr) -> Self {
+        let mods = pack.mods();
+        Self { pack, mods }
+    }
+}
+
+/// The state of the prompt to install a dropped mod.
+#[derive(Debug, Default)]
+pub struct InstallPrompt {
+    /// The levelpack (by path) picked to install into
+    pub target: Option<PathBuf>,
+    /// The mods in the target the dropped mod conflicts with (see [`ListedPack::conflicts_with`]).
+    ///
+    /// Checking for conflicts reads the mods' files, so this is only worked out when the prompt opens,
+    /// or once something changes which mods could conflict. Until then it is [`None`].
+    pub conflicts: Option<Vec<String>>,
 }
 
 impl AppState {
@@ -17,4 +93,76 @@ impl AppState {
     pub fn set_files(&mut self, files: BabaFiles) {
         self.files = Some(files);
     }
+    /// Whether an installation of Baba is You has been found yet.
+    ///
+    /// Until it has, the application stays on [`super::status::Status::Startup`].
+    pub fn has_files(&self) -> bool {
+        self.files.is_some()
+    }
+    /// Loads the palettes with `load`, unless they have already been loaded.
+    ///
+    /// Loading decodes every palette image, so it should only happen once rather than every frame.
+    /// Loaded palettes are never empty (the bundled palette is used if none are found), which is
+    /// how already loaded palettes are told apart.
+    pub fn ensure_palettes(
+        &mut self,
+        load: impl FnOnce() -> Result<Vec<ThemeData>, BabaError>,
+    ) -> Result<(), BabaError> {
+        if self.palettes.is_empty() {
+            self.palettes = load()?;
+        }
+        Ok(())
+    }
+    /// Reads the levelpacks (and their mods) of the installation from disk again.
+    ///
+    /// This is slow, so rather than every frame it's only done when the overview is first shown,
+    /// and after something that could change the packs (e.g. installing or merging mods).
+    pub fn refresh_packs(&mut self) -> Result<(), BabaError> {
+        // emptied first, so a refresh that fails isn't retried every frame
+        self.packs = Some(Vec::new());
+        let Some(files) = &self.files else {
+            return Ok(());
+        };
+        let packs = files.levelpacks(true)?;
+        self.packs = Some(packs.into_iter().map(ListedPack::from).collect());
+        // the installed mods may have changed, so any conflicts need to be found again
+        self.install_prompt.conflicts = None;
+        Ok(())
+    }
+    /// Records an error to be shown to the user.
+    ///
+    /// Only the most recent error is kept, so an error repeated every frame
+    /// replaces itself rather than piling up.
+    pub fn push_error(&mut self, error: impl Display) {
+        self.last_error = Some(error.to_string());
+    }
+    /// Hides the most recent error.
+    pub fn dismiss_error(&mut self) {
+        self.last_error = None;
+    }
+    /// Selects a mod if it isn't selected, and deselects it otherwise.
+    pub fn toggle_selected_mod(&mut self, path: PathBuf) {
+        match self
+            .selected_mods
+            .iter()
+            .position(|selected| *selected == path)
+        {
+            Some(index) => {
+                self.selected_mods.remove(index);
+            }
+            None => self.selected_mods.push(path),
+        }
+    }
+    /// Imports files dropped onto the window (see [`mod_from_dropped_file`]),
+    /// queueing up any mods to be installed.
+    ///
+    /// Files that aren't mods are rejected, leaving an error for the user.
+    pub fn receive_dropped_files(&mut self, files: &[DroppedFile]) {
+        for file in files {
+            match mod_from_dropped_file(file) {
+                Ok(baba_mod) => self.dropped_mods.push(baba_mod),
+                Err(error) => self.push_error(error),
+            }
+        }
+    }
 }
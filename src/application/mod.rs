@@ -1,15 +1,26 @@
-use std::fs;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
 
 use eframe::icon_data::from_png_bytes;
-use egui::{epaint::text::FontInsert, FontData, IconData};
+use egui::{
+    epaint::text::{FontInsert, FontPriority, InsertFontFamily},
+    DroppedFile, FontData, FontFamily, IconData,
+};
 use themedata::ThemeData;
 
-use crate::error::babaerror::BabaError;
+use crate::{
+    error::{applicationerror::ApplicationError, babaerror::BabaError},
+    levelpack::levelpackrepr::LevelpackRepr,
+    mods::{babamod::BabaMod, is_lua_file},
+};
 
 pub mod activeapp;
 pub mod app;
 pub mod appoptions;
 pub mod appstate;
+pub mod packsortorder;
 pub mod status;
 pub mod themedata;
 
@@ -25,36 +36,225 @@ pub fn load_image_from_path(path: &std::path::Path) -> Result<egui::ColorImage,
     ))
 }
 
+/// The name of the folder holding the program's own data, see [`find_data_dir`].
+pub const DATA_DIR: &str = "data";
+
+/// The name of the folder (inside of a data folder) that holds fonts.
+pub const FONTS_DIR: &str = "fonts";
+
+/// The name of the folder (inside of a data folder) that holds palettes.
+pub const PALETTES_DIR: &str = "palettes";
+
+/// The key [app::App] saves its [appoptions::AppOptions] under, between runs.
+pub const OPTIONS_KEY: &str = "options";
+
+/// The key [app::App] saves its [appstate::AppState] under, between runs.
+pub const STATE_KEY: &str = "state";
+
+/// The font used when the selected font can't be found.
+pub const DEFAULT_FONT: &str = "LibSans.ttf";
+
+/// The width and height of a single color when showing a theme in the settings.
+pub const SWATCH_SIZE: f32 = 16.0;
+
+/// The fonts bundled into the program, so that it can run without the data folder.
+const BUNDLED_FONTS: [(&str, &[u8]); 6] = [
+    ("Baba.ttf", include_bytes!("../data/fonts/Baba.ttf")),
+    ("Dyslexic.otf", include_bytes!("../data/fonts/Dyslexic.otf")),
+    (
+        "DyslexicMono.otf",
+        include_bytes!("../data/fonts/DyslexicMono.otf"),
+    ),
+    ("LibMono.ttf", include_bytes!("../data/fonts/LibMono.ttf")),
+    ("LibSans.ttf", include_bytes!("../data/fonts/LibSans.ttf")),
+    ("LibSerif.ttf", include_bytes!("../data/fonts/LibSerif.ttf")),
+];
+
+/// The palette bundled into the program, used when no palettes can be found on disk.
+const BUNDLED_PALETTE: &[u8] = include_bytes!("../data/palettes/Default.png");
+
+/// The icon of the program.
+const BUNDLED_ICON: &[u8] = include_bytes!("../data/icon.png");
+
+/// Finds the folder holding the program's own data (fonts, palettes, etc.) for the app with the given id.
+///
+/// This is the [`DATA_DIR`] folder next to the executable, or else the one in the platform's app-data folder
+/// (see [`eframe::storage_dir`]). Debug builds also look in the [`repository_data_dir`], so `cargo run` works as is.
+///
+/// Returns [`None`] if there is no data folder, in which case only the bundled fonts and palette are used.
+pub fn find_data_dir(app_id: &str) -> Option<PathBuf> {
+    let beside_executable = env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(DATA_DIR)));
+    let app_data = eframe::storage_dir(app_id).map(|dir| dir.join(DATA_DIR));
+    let candidates = [beside_executable, app_data].into_iter().flatten();
+    #[cfg(any(test, debug_assertions))]
+    let candidates = candidates.chain([repository_data_dir()]);
+    candidates.into_iter().find(|dir| dir.is_dir())
+}
+
+/// The data folder of the repository the program was built from.
+///
+/// Only exists in debug builds (and tests), so that release builds never depend on where they were built.
+#[cfg(any(test, debug_assertions))]
+pub fn repository_data_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("src")
+        .join(DATA_DIR)
+}
+
+/// Loads the fonts from the [`repository_data_dir`], see [`load_fonts_from`].
+#[cfg(any(test, debug_assertions))]
 pub fn load_fonts() -> Result<Vec<FontInsert>, BabaError> {
-    let mut result = Vec::new();
-    for file in fs::read_dir("src\\data\\fonts")? {
+    load_fonts_from(&repository_data_dir().join(FONTS_DIR))
+}
+
+/// Loads an image from its encoded bytes (e.g. the contents of a `.png`).
+pub fn load_image_from_bytes(bytes: &[u8]) -> Result<egui::ColorImage, image::ImageError> {
+    let image = image::load_from_memory(bytes)?;
+    let size = [image.width() as _, image.height() as _];
+    let image_buffer = image.to_rgba8();
+    let pixels = image_buffer.as_flat_samples();
+    Ok(egui::ColorImage::from_rgba_unmultiplied(
+        size,
+        pixels.as_slice(),
+    ))
+}
+
+/// Loads the bundled fonts, alongside every font inside of a folder.
+///
+/// Fonts on disk take the place of bundled fonts with the same name,
+/// and if the folder does not exist only the bundled fonts are returned.
+///
+/// Each font is given its own family (named after the font), and is added to
+/// [`FontFamily::Proportional`] as a fallback, see [`font_families`].
+pub fn load_fonts_from(dir: &Path) -> Result<Vec<FontInsert>, BabaError> {
+    let mut result = bundled_fonts();
+    if !dir.is_dir() {
+        return Ok(result);
+    }
+    for file in fs::read_dir(dir)? {
         let file = file?;
         let data = fs::read(file.path())?;
         let name = file.file_name().into_string().unwrap_or("".to_owned());
+        // fonts on disk replace their bundled counterparts
+        result.retain(|font| font.name != name);
         result.push(FontInsert {
+            families: font_families(&name),
             name,
             data: FontData::from_owned(data),
-            families: Vec::new(),
         })
     }
     Ok(result)
 }
 
+/// The fonts bundled into the program, see [`load_fonts_from`].
+pub fn bundled_fonts() -> Vec<FontInsert> {
+    BUNDLED_FONTS
+        .iter()
+        .map(|&(name, data)| FontInsert {
+            name: name.to_owned(),
+            data: FontData::from_static(data),
+            families: font_families(name),
+        })
+        .collect()
+}
+
+/// The families a loaded font belongs to: first in its own family ([`FontFamily::Name`]),
+/// and last in [`FontFamily::Proportional`], so that it only fills in glyphs other fonts are missing.
+fn font_families(name: &str) -> Vec<InsertFontFamily> {
+    vec![
+        InsertFontFamily {
+            family: FontFamily::Name(name.into()),
+            priority: FontPriority::Highest,
+        },
+        InsertFontFamily {
+            family: FontFamily::Proportional,
+            priority: FontPriority::Lowest,
+        },
+    ]
+}
+
+/// Loads the palettes from the [`repository_data_dir`], see [`load_themes_from`].
+#[cfg(any(test, debug_assertions))]
 pub fn load_themes() -> Result<Vec<ThemeData>, BabaError> {
+    load_themes_from(&repository_data_dir().join(PALETTES_DIR))
+}
+
+/// Loads every palette inside of a folder as a [`ThemeData`].
+///
+/// If the folder does not exist (or holds no palettes), the bundled default palette is loaded instead.
+pub fn load_themes_from(dir: &Path) -> Result<Vec<ThemeData>, BabaError> {
     let mut result = Vec::new();
-    for file in fs::read_dir("src\\data\\palettes")? {
-        let file = file?;
-        let theme = ThemeData::from_image_file(&file.path())?;
-        result.push(theme);
+    if dir.is_dir() {
+        for file in fs::read_dir(dir)? {
+            let file = file?;
+            let theme = ThemeData::from_image_file(&file.path())?;
+            result.push(theme);
+        }
+    }
+    if result.is_empty() {
+        result.push(bundled_theme()?);
     }
     Ok(result)
 }
 
+/// The palette bundled into the program, see [`load_themes_from`].
+pub fn bundled_theme() -> Result<ThemeData, BabaError> {
+    Ok(load_image_from_bytes(BUNDLED_PALETTE)?.try_into()?)
+}
+
+/// Imports a file dropped onto the window as a mod.
+///
+/// `.zip` archives are loaded via [`BabaMod::from_archive`], while mod folders
+/// and single `.lua` files are loaded in place via [`BabaMod::new`].
+///
+/// # Errors
+/// Returns [`ApplicationError::NotAMod`] for anything else (including dropped files without a path),
+/// or any error from extracting an archive.
+pub fn mod_from_dropped_file(file: &DroppedFile) -> Result<BabaMod, BabaError> {
+    let Some(path) = &file.path else {
+        return Err(ApplicationError::NotAMod(file.name.clone()))?;
+    };
+    let is_zip = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("zip"));
+    match (path.is_dir(), path.is_file()) {
+        (true, _) => Ok(BabaMod::new(path.clone())),
+        (_, true) if is_zip => BabaMod::from_archive(path),
+        (_, true) if is_lua_file(path) => Ok(BabaMod::new(path.clone())),
+        _ => Err(ApplicationError::NotAMod(path.display().to_string()))?,
+    }
+}
+
+/// Returns whether a levelpack should be shown in the overview when filtering by `query`.
+///
+/// The query is matched (case-insensitively, anywhere in the text) against the pack's name and author,
+/// and the IDs and tags of `mods`, the pack's mods. An empty query matches every pack.
+///
+/// This runs every frame, so `mods` should be the mods already loaded for the overview
+/// (see [`appstate::AppState::refresh_packs`]), rather than read from disk again.
+pub fn matches_filter(pack: &LevelpackRepr, mods: &[BabaMod], query: &str) -> bool {
+    let query = query.trim().to_lowercase();
+    let matches = |text: &str| text.to_lowercase().contains(&query);
+    if query.is_empty() || matches(&pack.name()) || matches(&pack.author()) {
+        return true;
+    }
+    mods.iter().any(|baba_mod| {
+        matches(&baba_mod.mod_id()) || baba_mod.tags().iter().any(|tag| matches(tag))
+    })
+}
+
 pub fn icon() -> Result<IconData, BabaError> {
-    let icon = fs::read("src\\data\\icon.png")?;
-    Ok(from_png_bytes(&icon)?)
+    Ok(from_png_bytes(BUNDLED_ICON)?)
 }
 
+/// The width (in pixels) of a baba palette image.
+pub const PALETTE_WIDTH: usize = 7;
+
+/// The height (in pixels) of a baba palette image.
+pub const PALETTE_HEIGHT: usize = 5;
+
 pub const fn pixel_index(x: usize, y: usize) -> usize {
-    (y * 7) + x
+    (y * PALETTE_WIDTH) + x
 }
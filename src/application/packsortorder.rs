@@ -0,0 +1,53 @@
+use std::{cmp::Ordering, fmt::Display};
+
+use serde::{Deserialize, Serialize};
+
+use crate::levelpack::levelpackrepr::LevelpackRepr;
+
+/// The order levelpacks are listed in on the overview, see [`compare_packs`].
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PackSortOrder {
+    #[default]
+    /// Alphabetically by name
+    Name,
+    /// Alphabetically by author
+    Author,
+    /// By the total number of collectibles (prizes, clears, and bonuses), fewest first
+    Collectibles,
+}
+
+impl PackSortOrder {
+    /// Every sort order, in the order they're offered to the user.
+    pub const ALL: [PackSortOrder; 3] = [Self::Name, Self::Author, Self::Collectibles];
+}
+
+impl Display for PackSortOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            PackSortOrder::Name => "Name",
+            PackSortOrder::Author => "Author",
+            PackSortOrder::Collectibles => "Collectibles",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+/// Compares two levelpacks by the given sort order. Names and authors are compared case-insensitively.
+///
+/// Meant for [`slice::sort_by`], which is stable, so packs that compare equal keep their order.
+pub fn compare_packs(
+    left: &LevelpackRepr,
+    right: &LevelpackRepr,
+    order: PackSortOrder,
+) -> Ordering {
+    let collectibles =
+        |pack: &LevelpackRepr| pack.prize_max() + pack.clear_max() + pack.bonus_max();
+    match order {
+        PackSortOrder::Name => left.name().to_lowercase().cmp(&right.name().to_lowercase()),
+        PackSortOrder::Author => left
+            .author()
+            .to_lowercase()
+            .cmp(&right.author().to_lowercase()),
+        PackSortOrder::Collectibles => collectibles(left).cmp(&collectibles(right)),
+    }
+}
@@ -18,6 +18,8 @@ pub enum Status {
 }
 
 impl Status {
+    /// Lays out the current screen. This runs every frame, so it only does layout;
+    /// setup is done once beforehand, by [`super::app::App::setup`].
     pub fn render(
         &mut self,
         ctx: &egui::Context,
@@ -25,8 +27,6 @@ impl Status {
         state: &mut AppState,
         options: &mut AppOptions,
     ) -> Result<(), BabaError> {
-        let mut active = ActiveApp::new(ctx, frame, state, self, options);
-        active.install_image_loaders();
-        active.render()
+        ActiveApp::new(ctx, frame, state, self, options).render()
     }
 }
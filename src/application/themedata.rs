@@ -1,11 +1,11 @@
-use std::path::Path;
+use std::{collections::BTreeMap, fmt::Display, path::Path};
 
-use egui::{ecolor::HexColor as Color, ColorImage};
+use egui::{ecolor::HexColor as Color, Color32, ColorImage};
 use serde::{Deserialize, Serialize};
 
 use crate::error::applicationerror::ApplicationError;
 
-use super::{load_image_from_path, pixel_index};
+use super::{load_image_from_path, pixel_index, PALETTE_HEIGHT, PALETTE_WIDTH};
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy)]
 pub struct ThemeData {
@@ -27,6 +27,23 @@ pub struct ThemeData {
 }
 
 impl ThemeData {
+    /// The names of the theme's colors, in the order they are declared.
+    pub const COLOR_NAMES: [&'static str; 13] = [
+        "dark",
+        "dark_accent",
+        "light",
+        "light_accent",
+        "grey",
+        "error",
+        "warning",
+        "accept",
+        "link",
+        "link_visited",
+        "spore",
+        "blossom",
+        "bonus",
+    ];
+
     pub fn new(data: [&str; 13]) -> Result<Self, ApplicationError> {
         Ok(Self {
             dark: data[0].parse()?,
@@ -44,10 +61,120 @@ impl ThemeData {
             bonus: data[12].parse()?,
         })
     }
+    /// Parses a theme from a list of thirteen hex codes, one per line, in the order the colors are declared.
+    /// Blank lines and whitespace around each code are ignored.
+    ///
+    /// # Errors
+    /// This function may error if:
+    /// - There weren't exactly thirteen codes ([`ApplicationError::ThemeColorCount`])
+    /// - Any of the codes is not a valid hex code ([`ApplicationError::ColorParsing`])
+    pub fn from_hex_lines(text: &str) -> Result<Self, ApplicationError> {
+        let lines: Vec<&str> = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect();
+        let count = lines.len();
+        let lines: [&str; 13] = lines
+            .try_into()
+            .map_err(|_| ApplicationError::ThemeColorCount(count))?;
+        Self::new(lines)
+    }
     pub fn from_image_file(file: &Path) -> Result<Self, ApplicationError> {
         let image = load_image_from_path(file)?;
         image.try_into()
     }
+
+    /// Gets all thirteen colors of the theme, in the order they are declared.
+    pub fn colors(&self) -> [Color32; 13] {
+        [
+            self.dark,
+            self.dark_accent,
+            self.light,
+            self.light_accent,
+            self.grey,
+            self.error,
+            self.warning,
+            self.accept,
+            self.link,
+            self.link_visited,
+            self.spore,
+            self.blossom,
+            self.bonus,
+        ]
+        .map(|color| color.color())
+    }
+
+    /// Gets every color of the theme as a hex code (e.g. `#2d2922`), keyed by its name.
+    pub fn to_hex_map(&self) -> BTreeMap<&'static str, String> {
+        Self::COLOR_NAMES
+            .into_iter()
+            .zip(self.colors().map(to_hex))
+            .collect()
+    }
+
+    /// Lays the theme back out as a baba palette image (7 pixels wide by 5 pixels tall).
+    ///
+    /// The thirteen colors are put back in the same places they are read from,
+    /// and every other pixel is filled with the dark color.
+    pub fn to_image(&self) -> ColorImage {
+        let mut pixels = vec![self.dark.color(); PALETTE_WIDTH * PALETTE_HEIGHT];
+        pixels[pixel_index(0, 0)] = self.dark.color();
+        pixels[pixel_index(1, 0)] = self.dark_accent.color();
+        pixels[pixel_index(0, 3)] = self.light.color();
+        pixels[pixel_index(0, 2)] = self.light_accent.color();
+        pixels[pixel_index(0, 1)] = self.grey.color();
+        pixels[pixel_index(2, 1)] = self.error.color();
+        pixels[pixel_index(2, 4)] = self.warning.color();
+        pixels[pixel_index(5, 2)] = self.accept.color();
+        pixels[pixel_index(1, 4)] = self.link.color();
+        pixels[pixel_index(1, 2)] = self.link_visited.color();
+        pixels[pixel_index(3, 4)] = self.spore.color();
+        pixels[pixel_index(4, 2)] = self.blossom.color();
+        pixels[pixel_index(4, 1)] = self.bonus.color();
+        ColorImage {
+            size: [PALETTE_WIDTH, PALETTE_HEIGHT],
+            pixels,
+        }
+    }
+
+    /// Saves the theme as a palette `.png` that baba can load.
+    pub fn save_palette(&self, path: &Path) -> Result<(), ApplicationError> {
+        let bytes: Vec<u8> = self
+            .to_image()
+            .pixels
+            .iter()
+            .flat_map(Color32::to_srgba_unmultiplied)
+            .collect();
+        image::save_buffer(
+            path,
+            &bytes,
+            PALETTE_WIDTH as u32,
+            PALETTE_HEIGHT as u32,
+            image::ColorType::Rgba8,
+        )?;
+        Ok(())
+    }
+}
+
+/// Writes a color as a hex code, leaving out the alpha if the color is opaque.
+fn to_hex(color: Color32) -> String {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    match a {
+        u8::MAX => format!("#{r:02x}{g:02x}{b:02x}"),
+        _ => format!("#{r:02x}{g:02x}{b:02x}{a:02x}"),
+    }
+}
+
+impl Display for ThemeData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lines: Vec<String> = Self::COLOR_NAMES
+            .into_iter()
+            .zip(self.colors())
+            .map(|(name, color)| format!("{name}: {}", to_hex(color)))
+            .collect();
+        write!(f, "{}", lines.join("\n"))
+    }
 }
 
 impl Default for ThemeData {
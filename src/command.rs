@@ -0,0 +1,118 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    error::{babaerror::BabaError, commanderror::CommandError},
+    files::babafiles::BabaFiles,
+    merge::mergeoptions::MergeOptions,
+    mods::babamod::BabaMod,
+};
+
+/// How to use the manager from the command line, shown when the arguments can't be understood.
+pub const USAGE: &str = "Usage: babamodmanager --merge <mod> <mod> --out <folder> [--baba <folder>]
+    --merge  The two mods to merge
+    --out    The folder to put the merged mod in
+    --baba   Where Baba is You is installed (found automatically if left out)
+Without any arguments, the manager's window is opened instead.";
+
+/// Something to be done from the command line, without opening a window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Merges two mods into a new mod inside of `out`, see [`merge_paths`]
+    Merge {
+        left: PathBuf,
+        right: PathBuf,
+        out: PathBuf,
+        /// Where baba is installed, if given
+        baba: Option<PathBuf>,
+    },
+}
+
+impl Command {
+    /// Reads a command from the command line's arguments (not including the program's name).
+    ///
+    /// If no arguments were given, there is no command, and the window should be opened instead.
+    ///
+    /// # Errors
+    /// This function may error if:
+    /// - A flag is missing its value(s) ([`CommandError::MissingValue`])
+    /// - An argument isn't understood ([`CommandError::UnexpectedArgument`])
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Option<Self>, CommandError> {
+        let mut args = args.into_iter();
+        let Some(first) = args.next() else {
+            return Ok(None);
+        };
+        if first != "--merge" {
+            return Err(CommandError::UnexpectedArgument(first));
+        }
+        let (Some(left), Some(right)) = (args.next(), args.next()) else {
+            return Err(CommandError::MissingValue(first, 2));
+        };
+        let mut out = None;
+        let mut baba = None;
+        while let Some(flag) = args.next() {
+            let value = match flag.as_str() {
+                "--out" => &mut out,
+                "--baba" => &mut baba,
+                _ => return Err(CommandError::UnexpectedArgument(flag)),
+            };
+            *value = Some(
+                args.next()
+                    .map(PathBuf::from)
+                    .ok_or(CommandError::MissingValue(flag, 1))?,
+            );
+        }
+        let out = out.ok_or(CommandError::MissingValue("--out".to_owned(), 1))?;
+        Ok(Some(Command::Merge {
+            left: left.into(),
+            right: right.into(),
+            out,
+            baba,
+        }))
+    }
+
+    /// Runs the command.
+    ///
+    /// # Errors
+    /// This function may error if baba's installation can't be found, or the command itself fails.
+    pub fn run(&self) -> Result<(), BabaError> {
+        match self {
+            Command::Merge {
+                left,
+                right,
+                out,
+                baba,
+            } => {
+                let files = match baba {
+                    Some(path) => BabaFiles::from_raw_checked(path.clone())?,
+                    None => BabaFiles::detect()?,
+                };
+                merge_paths(left, right, out, &files)?;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Merges the mods at two paths into a new mod inside of `out`, as done by `--merge`.
+///
+/// # Errors
+/// This function may error if:
+/// - Either mod doesn't exist ([`CommandError::ModNotFound`])
+/// - The mods could not be merged (see [`BabaMod::merge_with`])
+pub fn merge_paths(
+    left: &Path,
+    right: &Path,
+    out: &Path,
+    files: &BabaFiles,
+) -> Result<BabaMod, BabaError> {
+    for path in [left, right] {
+        if !path.exists() {
+            return Err(CommandError::ModNotFound(path.to_owned()).into());
+        }
+    }
+    let options = MergeOptions {
+        location: out.to_owned(),
+        ..Default::default()
+    };
+    BabaMod::new(left.to_owned()).merge_with(&BabaMod::new(right.to_owned()), files, options)
+}
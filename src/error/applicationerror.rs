@@ -5,12 +5,16 @@ use thiserror::Error;
 pub enum ApplicationError {
     #[error("Attempt to parse a hex code failed")]
     ColorParsing(ParseHexColorError),
+    #[error("A theme needs exactly 13 colors, but {0} were given")]
+    ThemeColorCount(usize),
     #[error("The given image was either too large or too small (most likely the latter)")]
     ImageSize,
     #[error("Error when working with images")]
     ImageError(#[from] image::ImageError),
-    #[error("Could not select a currently loaded font")]
-    FontUnavailible
+    #[error("The font \"{0}\" is not loaded")]
+    FontUnavailable(String),
+    #[error("{0} is not a mod (only .zip archives, mod folders, and .lua files can be installed)")]
+    NotAMod(String),
 }
 
 impl From<ParseHexColorError> for ApplicationError {
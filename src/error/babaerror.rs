@@ -1,10 +1,15 @@
+#[cfg(feature = "gui")]
 use image::ImageError;
 use thiserror::Error;
 
 use crate::error::moddingerror::ModdingError;
-use std::{fmt::Display, io};
+use std::{fmt::Display, io, path::PathBuf};
 
-use super::{applicationerror::ApplicationError, levelpackerror::LevelpackError};
+#[cfg(feature = "gui")]
+use super::applicationerror::ApplicationError;
+use super::{
+    commanderror::CommandError, installerror::InstallError, levelpackerror::LevelpackError,
+};
 
 /// A generic error that holds any given error that the program may arise
 #[derive(Debug, Error)]
@@ -13,6 +18,8 @@ pub enum BabaError {
     Levelpack(#[from] LevelpackError),
     /// There was an error when using [`io`] or working with files
     IO(io::Error),
+    /// There was an error when using [`io`] on a specific file, see [`BabaError::io_at`]
+    IoAt { path: PathBuf, source: io::Error },
     /// There was an error when fetching or working with mods
     Modding(#[from] ModdingError),
     /// There was an error when using [`serde_json`]
@@ -20,9 +27,27 @@ pub enum BabaError {
     /// There was an error when using [`diff_match_patch_rs`]
     Dmp(diff_match_patch_rs::Error),
     /// An error arose from the application itself (usually the UI side of things)
+    #[cfg(feature = "gui")]
     Application(#[from] ApplicationError),
     /// An error came from eframe
+    #[cfg(feature = "gui")]
     EFrame(#[from] eframe::Error),
+    /// There was an error when reading or writing a `.zip` archive
+    Zip(zip::result::ZipError),
+    /// There was an issue with finding an installation of baba
+    Install(#[from] InstallError),
+    /// The arguments given on the command line could not be understood
+    Command(#[from] CommandError),
+}
+
+impl BabaError {
+    /// Wraps an [`io::Error`] with the path it happened on, so the user can be told which file is at fault.
+    ///
+    /// Meant to be used with [`Result::map_err`], e.g. `fs::read_to_string(&path).map_err(BabaError::io_at(&path))?`
+    pub fn io_at(path: impl Into<PathBuf>) -> impl FnOnce(io::Error) -> Self {
+        let path = path.into();
+        move |source| Self::IoAt { path, source }
+    }
 }
 
 impl From<diff_match_patch_rs::Error> for BabaError {
@@ -43,6 +68,13 @@ impl From<serde_json::Error> for BabaError {
     }
 }
 
+impl From<zip::result::ZipError> for BabaError {
+    fn from(value: zip::result::ZipError) -> Self {
+        Self::Zip(value)
+    }
+}
+
+#[cfg(feature = "gui")]
 impl From<ImageError> for BabaError {
     fn from(value: ImageError) -> Self {
         BabaError::Application(ApplicationError::ImageError(value))
@@ -54,11 +86,22 @@ impl Display for BabaError {
         let message = match self {
             BabaError::Levelpack(levelpack_error) => format!("{}", levelpack_error),
             BabaError::IO(error) => format!("Error when working with io:\n{}", error),
+            BabaError::IoAt { path, source } => {
+                format!(
+                    "Error when working with the file at {:?}:\n{}",
+                    path, source
+                )
+            }
             BabaError::Modding(modding_error) => format!("{}", modding_error),
             BabaError::SerdeJson(error) => format!("Error when parsing json:\n{}", error),
             BabaError::Dmp(error) => format!("Error when merging files:\n{:#?}", error),
+            #[cfg(feature = "gui")]
             BabaError::Application(application_error) => format!("Application error:\n{}", application_error),
+            #[cfg(feature = "gui")]
             BabaError::EFrame(error) => format!("Eframe error:\n{}", error),
+            BabaError::Zip(error) => format!("Error when working with a zip archive:\n{}", error),
+            BabaError::Install(install_error) => format!("{}", install_error),
+            BabaError::Command(command_error) => format!("{}", command_error),
         };
         write!(f, "{}", message)
     }
@@ -0,0 +1,31 @@
+use std::{fmt::Display, path::PathBuf};
+
+use thiserror::Error;
+
+/// An error arised when reading the arguments given on the command line
+#[derive(Debug, Error)]
+pub enum CommandError {
+    /// The given flag needs (at least) the given number of values after it
+    MissingValue(String, usize),
+    /// An argument was given that isn't understood
+    UnexpectedArgument(String),
+    /// There is no mod at the given path
+    ModNotFound(PathBuf),
+}
+
+impl Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            CommandError::MissingValue(flag, count) => {
+                format!("{} needs {} value(s) after it.", flag, count)
+            }
+            CommandError::UnexpectedArgument(argument) => {
+                format!("Did not understand the argument {:?}.", argument)
+            }
+            CommandError::ModNotFound(path) => {
+                format!("There is no mod at {:?}.", path)
+            }
+        };
+        write!(f, "{}", message)
+    }
+}
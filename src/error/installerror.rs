@@ -0,0 +1,45 @@
+use std::{fmt::Display, path::PathBuf};
+
+use thiserror::Error;
+
+/// An error arised when looking for (or checking) an installation of Baba is You
+#[derive(Debug, Error)]
+pub enum InstallError {
+    /// No installation could be found, after looking in every listed folder
+    NotDetected(Vec<PathBuf>),
+    /// The given folder has no `Data` folder inside of it
+    MissingDataFolder(PathBuf),
+    /// The given folder's `Data` folder only holds the given number of baba's lua files
+    TooFewNativeFiles(PathBuf, usize),
+}
+
+impl Display for InstallError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            InstallError::NotDetected(tried) => {
+                let tried = tried
+                    .iter()
+                    .map(|path| format!("- {:?}", path))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                format!(
+                    "Could not find an installation of Baba is You. The following folders were checked:\n{}",
+                    tried
+                )
+            }
+            InstallError::MissingDataFolder(path) => {
+                format!(
+                    "The folder at {:?} does not look like an installation of Baba is You, as it has no Data folder.",
+                    path
+                )
+            }
+            InstallError::TooFewNativeFiles(path, found) => {
+                format!(
+                    "The folder at {:?} does not look like an installation of Baba is You, as only {} of the game's lua files were found.",
+                    path, found
+                )
+            }
+        };
+        write!(f, "{}", message)
+    }
+}
@@ -1,4 +1,7 @@
+#[cfg(feature = "gui")]
+pub mod applicationerror;
 pub mod babaerror;
+pub mod commanderror;
+pub mod installerror;
 pub mod levelpackerror;
 pub mod moddingerror;
-pub mod applicationerror;
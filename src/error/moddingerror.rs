@@ -9,8 +9,13 @@ pub enum ModdingError {
     NotAConfigFile(PathBuf),
     /// The specified string could not be parsed into a function
     NotALuaFunction(String),
-    /// While merging functions, the rename could not properly be specified
-    RenameError,
+    /// While merging functions, the rename could not properly be specified.
+    /// Holds the function, and the names it was renamed to (empty if no rename could be found,
+    /// or more than one if the files rename it differently)
+    RenameError {
+        function: String,
+        names: Vec<String>,
+    },
     /// While merging functions, the given function was not a baba function,
     /// despite having been declared one
     NotABabaFunction,
@@ -18,6 +23,34 @@ pub enum ModdingError {
     CodeRemoval,
     /// While patching together functions, at least one patch didn't work correctly
     IncompletePatching,
+    /// No `Sprites` folder could be found above the mod at the given path
+    SpritesFolderNotFound(PathBuf),
+    /// The archive at the given path does not contain a mod
+    NoModInArchive(PathBuf),
+    /// The mod could not be installed, as it clashes with the mods (by ID) listed
+    InstallConflict(Vec<String>),
+    /// The mod at the given path is not installed in the levelpack it was removed from
+    NotInLevelpack(PathBuf),
+    /// The lua file at the given path is not valid UTF-8, and needs to be re-encoded
+    NonUtf8Lua(PathBuf),
+    /// A mod already exists at the given path, and overwriting was not allowed
+    MergeTargetExists(PathBuf),
+    /// The mods (by ID) listed all use the init file at the given path
+    DuplicateInit(PathBuf, Vec<String>),
+    /// More than one mod uses the given mod ID
+    DuplicateModId(String),
+    /// The mods (by ID) listed all declare the given sprite
+    SpriteCollision(String, Vec<String>),
+    /// The given url could not be fetched, for the given reason
+    FetchFailed(String, String),
+    /// Merging produced lua that isn't properly structured.
+    /// Holds where and why it was rejected, and the offending line of code
+    MergeProducedInvalidLua(String, String),
+    /// Only the given number of mods were given to merge, when at least two are needed
+    NotEnoughMods(usize),
+    /// Merging a mod (the second value) into the mods merged before it (the first value) failed,
+    /// for the given reason
+    MergeStepFailed(Vec<String>, String, String),
 }
 
 impl Display for ModdingError {
@@ -35,9 +68,17 @@ impl Display for ModdingError {
                     str
                 )
             }
-            ModdingError::RenameError => {
-                "There was an error when attempting to preform a rename while merging".to_string()
-            }
+            ModdingError::RenameError { function, names } => match names.as_slice() {
+                [] => format!(
+                    "There was an error when attempting to preform a rename of {} while merging",
+                    function
+                ),
+                names => format!(
+                    "The function {} is renamed differently by the files being merged: {}",
+                    function,
+                    names.join(", ")
+                ),
+            },
             ModdingError::NotABabaFunction => {
                 "The given function was not a baba function, despite being declared one.".to_string()
             }
@@ -47,6 +88,79 @@ impl Display for ModdingError {
             ModdingError::IncompletePatching => {
                 "The two mods could not be properly merged, as at least one patch could not be applied correctly.".to_string()
             }
+            ModdingError::NoModInArchive(path_buf) => {
+                format!(
+                    "The archive at {:?} does not contain a config file or lua file to load as a mod.",
+                    path_buf
+                )
+            }
+            ModdingError::InstallConflict(mods) => {
+                format!(
+                    "The mod could not be installed, as it conflicts with the following mods: {}",
+                    mods.join(", ")
+                )
+            }
+            ModdingError::NotInLevelpack(path_buf) => {
+                format!(
+                    "The mod at {:?} is not installed in this levelpack.",
+                    path_buf
+                )
+            }
+            ModdingError::SpritesFolderNotFound(path_buf) => {
+                format!(
+                    "Could not find a Sprites folder for the mod at {:?}.",
+                    path_buf
+                )
+            }
+            ModdingError::NonUtf8Lua(path_buf) => {
+                format!(
+                    "The lua file at {:?} is not encoded as UTF-8, and needs to be re-encoded before it can be read.",
+                    path_buf
+                )
+            }
+            ModdingError::MergeTargetExists(path_buf) => {
+                format!(
+                    "A mod already exists at {:?}. Enable overwriting to replace it.",
+                    path_buf
+                )
+            }
+            ModdingError::DuplicateInit(path_buf, mods) => {
+                format!(
+                    "The following mods all use the init file at {:?}, and will overwrite each other: {}",
+                    path_buf,
+                    mods.join(", ")
+                )
+            }
+            ModdingError::DuplicateModId(id) => {
+                format!("More than one mod uses the mod ID \"{}\".", id)
+            }
+            ModdingError::SpriteCollision(sprite, mods) => {
+                format!(
+                    "The following mods all declare the sprite \"{}\": {}",
+                    sprite,
+                    mods.join(", ")
+                )
+            }
+            ModdingError::FetchFailed(url, reason) => {
+                format!("Could not fetch {}: {}", url, reason)
+            }
+            ModdingError::MergeProducedInvalidLua(problem, region) => {
+                format!(
+                    "The merged code is not valid lua ({}):\n{}",
+                    problem, region
+                )
+            }
+            ModdingError::NotEnoughMods(count) => {
+                format!("At least two mods are needed to merge, but {} were given.", count)
+            }
+            ModdingError::MergeStepFailed(merged, next, reason) => {
+                format!(
+                    "Could not merge {} into {}:\n{}",
+                    next,
+                    merged.join(", "),
+                    reason
+                )
+            }
         };
         write!(f, "{}", message)
     }
@@ -1,22 +1,36 @@
-use std::{fs, io, path::PathBuf};
+use std::{
+    cell::OnceCell,
+    collections::{BTreeMap, HashMap, HashSet},
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    error::{babaerror::BabaError, levelpackerror::LevelpackError},
-    levelpack::levelpackrepr::LevelpackRepr, mods::luafunction::LuaFunction,
+    error::{babaerror::BabaError, installerror::InstallError, levelpackerror::LevelpackError},
+    levelpack::{levelpackfile::LevelpackFile, levelpackrepr::LevelpackRepr},
+    mods::{babamod::BabaMod, luafunction::LuaFunction, mods_in_directory},
 };
 
 use super::{
-    editorfuncs::editor_functions, luafile::LuaFile, BABA_LUA_FILE_NAMES, RESERVED_PACK_NAMES,
-    STEAM_PATH,
+    copy_recursively, editorfuncs::editor_functions, launcher::Launcher, luafile::LuaFile,
+    BABA_LUA_FILE_NAMES, BACKUP_FOLDER_PREFIX, MIN_NATIVE_FILES, RESERVED_PACK_NAMES, STEAM_PATH,
 };
 
+/// The levelpacks that loaded, alongside the folders of those that failed to (and why),
+/// see [`BabaFiles::levelpacks_verbose`].
+pub type LoadedLevelpacks = (Vec<LevelpackRepr>, Vec<(PathBuf, BabaError)>);
+
 /// A representation of the Baba is You file structure.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BabaFiles {
     // The path to the root folder (that contains the .exe)
     path: PathBuf,
+    /// Every function in baba's own lua files by name, see [`BabaFiles::native_function`]
+    #[serde(skip)]
+    native_functions: OnceCell<HashMap<String, LuaFunction>>,
 }
 
 impl BabaFiles {
@@ -24,7 +38,29 @@ impl BabaFiles {
     /// This is not usually reccomended, but is
     /// required for e.g. Itch.io installations
     pub fn from_raw(path: PathBuf) -> Self {
-        Self { path }
+        Self {
+            path,
+            native_functions: OnceCell::new(),
+        }
+    }
+    /// Creates a BabaFiles from a raw path to the root, after checking that it looks like an installation of baba.
+    ///
+    /// # Errors
+    /// - Returns [`InstallError::MissingDataFolder`] if the path has no `Data` folder
+    /// - Returns [`InstallError::TooFewNativeFiles`] if the `Data` folder holds fewer than [`MIN_NATIVE_FILES`] of baba's lua files
+    pub fn from_raw_checked(path: PathBuf) -> Result<Self, BabaError> {
+        let this = Self::from_raw(path);
+        if !this.path.join("Data").is_dir() {
+            return Err(InstallError::MissingDataFolder(this.path).into());
+        }
+        let found = BABA_LUA_FILE_NAMES
+            .iter()
+            .filter(|&&name| this.native_file_path(name).is_file())
+            .count();
+        if found < MIN_NATIVE_FILES {
+            return Err(InstallError::TooFewNativeFiles(this.path, found).into());
+        }
+        Ok(this)
     }
     /// Creates a BabaFiles by looking for the Baba installation from steam.
     ///
@@ -41,10 +77,44 @@ impl BabaFiles {
         }
     }
 
+    /// Looks for an installation of Baba is You in the default folders of every
+    /// supported [`Launcher`], returning the first one found.
+    ///
+    /// Steam is tried first (see [`BabaFiles::from_steam`]), then the itch.io app, then GOG Galaxy.
+    ///
+    /// # Errors
+    /// Returns [`InstallError::NotDetected`] listing every folder that was checked, if none held an installation
+    pub fn detect() -> Result<Self, BabaError> {
+        let mut tried = Vec::new();
+        for launcher in Launcher::ALL {
+            for path in launcher.candidate_paths() {
+                if let Ok(files) = Self::from_raw_checked(path.clone()) {
+                    return Ok(files);
+                }
+                tried.push(path);
+            }
+        }
+        Err(InstallError::NotDetected(tried))?
+    }
+
     /// Fetches the directory for global mods
     pub fn global_mods_dir(&self) -> PathBuf {
         self.path.join("Lua")
     }
+    /// Fetches baba's own sprites directory, where the sprites of global mods go
+    pub fn global_sprites_dir(&self) -> PathBuf {
+        self.path.join("Data").join("Sprites")
+    }
+    /// Fetches the list of globally installed mods, from [`BabaFiles::global_mods_dir`].
+    ///
+    /// Any native baba files that live in the directory are skipped.
+    ///
+    /// # Errors
+    /// This function may error if the directory could not be read ([`io::Error`])
+    pub fn global_mods(&self) -> Result<Vec<BabaMod>, BabaError> {
+        mods_in_directory(&self.global_mods_dir())
+    }
+
     /// Fetches the directory for levelpacks
     ///
     /// # Errors
@@ -71,11 +141,29 @@ impl BabaFiles {
         &self,
         respect_reserved_names: bool,
     ) -> Result<Vec<LevelpackRepr>, BabaError> {
+        let (packs, _) = self.levelpacks_verbose(respect_reserved_names)?;
+        Ok(packs)
+    }
+
+    /// Fetches a list of levelpacks from the `Worlds` directory, alongside
+    /// any levelpacks that failed to load.
+    ///
+    /// Unlike [`BabaFiles::levelpacks`], packs that could not be parsed are not
+    /// silently dropped, and are instead returned as the second element of the tuple,
+    /// paired with the path to the pack.
+    ///
+    /// # Errors
+    /// See [`BabaFiles::levelpacks`].
+    pub fn levelpacks_verbose(
+        &self,
+        respect_reserved_names: bool,
+    ) -> Result<LoadedLevelpacks, BabaError> {
         // get the directory for the levelpacks
         let path = self.levelpacks_dir()?;
         let path_iter = path.read_dir()?;
-        // create a list of levelpacks
+        // create a list of levelpacks, and a list of failures
         let mut result = Vec::new();
+        let mut failures = Vec::new();
 
         // before we iterate over the entries, check to see if any actually exist
         let iter = path_iter.flatten().collect::<Vec<_>>();
@@ -97,30 +185,202 @@ impl BabaFiles {
                 }
             }
             // create a Levelpack from the folder
-            let Ok(pack) = LevelpackRepr::new(path) else {
-                continue;
-            };
-            // add it to the list
-            result.push(pack);
+            match LevelpackRepr::new(path.clone()) {
+                Ok(pack) => result.push(pack),
+                Err(error) => failures.push((path, error)),
+            }
+        }
+        Ok((result, failures))
+    }
+
+    /// Finds levelpacks that share the same display name (the `name` in their `world_data.txt`),
+    /// which would otherwise be impossible to tell apart in a list.
+    ///
+    /// Each name shared by more than one pack is returned with the folders of those packs,
+    /// sorted by name (and then by folder). Reserved packs are skipped, as in the application.
+    ///
+    /// # Errors
+    /// See [`BabaFiles::levelpacks`].
+    pub fn duplicate_pack_names(&self) -> Result<Vec<(String, Vec<PathBuf>)>, BabaError> {
+        let mut names: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+        for pack in self.levelpacks(true)? {
+            names
+                .entry(pack.name())
+                .or_default()
+                .push(pack.path().clone());
+        }
+        Ok(names
+            .into_iter()
+            .filter(|(_, paths)| paths.len() > 1)
+            .map(|(name, mut paths)| {
+                paths.sort();
+                (name, paths)
+            })
+            .collect())
+    }
+
+    /// Snapshots the mods of the game and of a levelpack, so that they can be rolled back
+    /// with [`BabaFiles::restore`] if something (such as a merge) goes wrong.
+    ///
+    /// The global `Lua` folder, and the pack's `Lua` and `Sprites` folders, are copied into a new
+    /// timestamped folder inside of `dest` (e.g. `[dest]\backup_1700000000`), laid out the same way
+    /// as they are in the game's folder. Folders that don't exist are skipped.
+    /// The path to the new backup is returned.
+    ///
+    /// # Errors
+    /// This function may error if any of the folders could not be copied ([`io::Error`])
+    pub fn backup(&self, pack: &LevelpackRepr, dest: &Path) -> Result<PathBuf, BabaError> {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_secs())
+            .unwrap_or_default();
+        let mut backup = dest.join(format!("{}{}", BACKUP_FOLDER_PREFIX, seconds));
+        // two backups within the same second shouldn't overwrite each other
+        let mut copy = 1;
+        while backup.exists() {
+            copy += 1;
+            backup = dest.join(format!("{}{}_{}", BACKUP_FOLDER_PREFIX, seconds, copy));
         }
-        Ok(result)
+        fs::create_dir_all(&backup)?;
+
+        let pack_folder = Path::new("Data")
+            .join("Worlds")
+            .join(pack.path().file_name().unwrap_or_default());
+        let folders = [
+            (self.global_mods_dir(), PathBuf::from("Lua")),
+            (
+                pack.pack_file(LevelpackFile::Lua),
+                pack_folder.join(String::from(LevelpackFile::Lua)),
+            ),
+            (
+                pack.pack_file(LevelpackFile::Sprites),
+                pack_folder.join(String::from(LevelpackFile::Sprites)),
+            ),
+        ];
+        for (from, to) in folders {
+            if from.is_dir() {
+                copy_recursively(&from, &backup.join(to))?;
+            }
+        }
+        Ok(backup)
     }
 
+    /// Rolls the game's folder back to a backup made by [`BabaFiles::backup`].
+    ///
+    /// Every folder in the backup replaces the folder it was copied from, so anything
+    /// added since the backup was made is removed, and anything removed is put back.
+    ///
+    /// # Errors
+    /// This function may error if the backup could not be read, or the folders could not be replaced ([`io::Error`])
+    pub fn restore(&self, backup_dir: &Path) -> Result<(), BabaError> {
+        // make sure the backup is actually there, rather than quietly restoring nothing
+        fs::metadata(backup_dir).map_err(BabaError::io_at(backup_dir))?;
+        let mut folders = Vec::new();
+        if backup_dir.join("Lua").is_dir() {
+            folders.push(PathBuf::from("Lua"));
+        }
+        let worlds = Path::new("Data").join("Worlds");
+        if backup_dir.join(&worlds).is_dir() {
+            for entry in backup_dir
+                .join(&worlds)
+                .read_dir()
+                .map_err(BabaError::io_at(backup_dir.join(&worlds)))?
+            {
+                let pack_folder = worlds.join(entry?.file_name());
+                for kind in [LevelpackFile::Lua, LevelpackFile::Sprites] {
+                    let folder = pack_folder.join(String::from(kind));
+                    if backup_dir.join(&folder).is_dir() {
+                        folders.push(folder);
+                    }
+                }
+            }
+        }
+        for folder in folders {
+            let live = self.path.join(&folder);
+            if live.exists() {
+                fs::remove_dir_all(&live)?;
+            }
+            copy_recursively(&backup_dir.join(&folder), &live)?;
+        }
+        Ok(())
+    }
+
+    /// The path to one of baba's own lua files, by name (see [`BABA_LUA_FILE_NAMES`])
+    fn native_file_path(&self, name: &str) -> PathBuf {
+        self.path.join("Data").join(format!("{}.lua", name))
+    }
+
+    /// Checks that every one of baba's own lua files can be read.
+    ///
+    /// [`BabaFiles::native_baba_lua_files`] skips over files it can't read, so this
+    /// can be used to warn about a partial installation before merging against it.
+    ///
+    /// # Errors
+    /// Returns the names of every native file that is missing or unreadable.
+    pub fn verify_native_files(&self) -> Result<(), Vec<String>> {
+        let missing: Vec<String> = BABA_LUA_FILE_NAMES
+            .iter()
+            .filter(|&&name| fs::read_to_string(self.native_file_path(name)).is_err())
+            .map(|&name| name.to_owned())
+            .collect();
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(missing)
+        }
+    }
+
+    /// Reads and parses baba's own lua files (see [`BABA_LUA_FILE_NAMES`]), alongside the editor's functions.
+    ///
+    /// The files are big, so with the `parallel` feature they are parsed in parallel (see [`LuaFile::read_all`]).
     pub fn native_baba_lua_files(&self) -> Vec<LuaFile> {
-        BABA_LUA_FILE_NAMES
+        let paths: Vec<PathBuf> = BABA_LUA_FILE_NAMES
             .iter()
-            .map(|&name| self.path.join("Data").join(format!("{}.lua", name)))
-            .flat_map(fs::read_to_string)
-            .chain(editor_functions().unwrap_or_default())
-            .map(Into::into)
-            .collect()
+            .map(|&name| self.native_file_path(name))
+            .collect();
+        let mut files = LuaFile::read_all(&paths);
+        files.extend(
+            editor_functions()
+                .unwrap_or_default()
+                .into_iter()
+                .map(Into::into),
+        );
+        files
     }
 
+    /// Looks up a function defined in one of baba's own lua files by name.
+    ///
+    /// The native files are only read and parsed the first time this is called,
+    /// every lookup afterwards is a map access.
+    pub fn native_function(&self, name: &str) -> Option<LuaFunction> {
+        self.native_functions
+            .get_or_init(|| {
+                // like `native_baba_lua_functions`, the first definition found wins
+                let mut functions = HashMap::new();
+                for func in self
+                    .native_baba_lua_files()
+                    .into_iter()
+                    .flat_map(|file| file.functions())
+                {
+                    functions.entry(func.definition().name()).or_insert(func);
+                }
+                functions
+            })
+            .get(name)
+            .cloned()
+    }
+
+    /// Collects every baba-native function from baba's own lua files into one list.
+    ///
+    /// Functions defined in more than one file are only listed once (the first definition found),
+    /// which is what [`crate::merge::merge_mods`] expects to compare mods against.
     pub fn native_baba_lua_functions(&self) -> Vec<LuaFunction> {
+        let mut seen = HashSet::new();
         self.native_baba_lua_files()
             .into_iter()
             .flat_map(|file| file.functions())
             .filter(|func| func.definition().is_baba_native())
+            .filter(|func| seen.insert(func.definition()))
             .collect()
     }
 }
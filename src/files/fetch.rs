@@ -0,0 +1,144 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{babaerror::BabaError, moddingerror::ModdingError},
+    mods::manifest::sha256_hex,
+};
+
+/// How long a downloaded file is used for before [`fetch_cached`] downloads it again.
+pub const CACHE_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The name of the file (inside of a cache folder) recording which url was cached as which file.
+pub const CACHE_INDEX_FILE_NAME: &str = "index.json";
+
+/// A downloaded url, as recorded in the cache's index.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    /// The file the url was saved as (see [`cache_file_name`])
+    file: String,
+    /// When the url was downloaded (in seconds since the unix epoch)
+    fetched_at: u64,
+}
+
+/// Resolves a url to a file on disk, downloading it into `cache_dir` if needed.
+///
+/// - `file://` urls and bare paths are local, so they are returned as-is (without a network call)
+/// - `http://` and `https://` urls are downloaded and stored with [`store_in_cache`].
+///   Later calls with the same url return the cached file, until it is older than [`CACHE_MAX_AGE`].
+///   If downloading it again fails, the old file is returned instead.
+///
+/// # Errors
+/// - Returns [`ModdingError::FetchFailed`] if a local file does not exist, or a download failed with nothing cached
+///   (downloading always fails if the `network` feature is disabled)
+/// - Returns an [`std::io::Error`] if the downloaded file could not be written
+pub fn fetch_cached(url: &str, cache_dir: &Path) -> Result<PathBuf, BabaError> {
+    if !(url.starts_with("http://") || url.starts_with("https://")) {
+        let path = PathBuf::from(url.strip_prefix("file://").unwrap_or(url));
+        if !path.exists() {
+            return Err(ModdingError::FetchFailed(
+                url.to_owned(),
+                "the file does not exist".to_owned(),
+            )
+            .into());
+        }
+        return Ok(path);
+    }
+    let cached = read_cache_index(cache_dir)
+        .get(url)
+        .map(|entry| (cache_dir.join(&entry.file), entry.fetched_at))
+        .filter(|(path, _)| path.is_file());
+    if let Some((path, fetched_at)) = &cached {
+        if seconds_since_epoch().saturating_sub(*fetched_at) < CACHE_MAX_AGE.as_secs() {
+            return Ok(path.clone());
+        }
+    }
+    match download(url) {
+        Ok(bytes) => store_in_cache(cache_dir, url, &bytes),
+        // an outdated file is better than no file at all
+        Err(error) => cached.map(|(path, _)| path).ok_or(error),
+    }
+}
+
+/// Saves the contents of a url into `cache_dir`, returning the path it was saved to.
+///
+/// Files are named after a hash of their contents (see [`cache_file_name`]), so identical files
+/// from different urls are only stored once. If the url previously held something else,
+/// the old file is removed (unless another url still uses it).
+///
+/// # Errors
+/// Returns an [`std::io::Error`] if the file or the cache's index could not be written.
+pub fn store_in_cache(cache_dir: &Path, url: &str, bytes: &[u8]) -> Result<PathBuf, BabaError> {
+    fs::create_dir_all(cache_dir)?;
+    let file = cache_file_name(url, bytes);
+    let path = cache_dir.join(&file);
+    if !path.is_file() {
+        fs::write(&path, bytes)?;
+    }
+    let mut index = read_cache_index(cache_dir);
+    let entry = CacheEntry {
+        file,
+        fetched_at: seconds_since_epoch(),
+    };
+    if let Some(old) = index.insert(url.to_owned(), entry) {
+        let still_used = index.values().any(|entry| entry.file == old.file);
+        if !still_used {
+            let _ = fs::remove_file(cache_dir.join(old.file));
+        }
+    }
+    fs::write(
+        cache_dir.join(CACHE_INDEX_FILE_NAME),
+        serde_json::to_string_pretty(&index)?,
+    )?;
+    Ok(path)
+}
+
+/// Reads which url was cached as which file. A missing (or unreadable) index is treated as empty.
+fn read_cache_index(cache_dir: &Path) -> BTreeMap<String, CacheEntry> {
+    fs::read_to_string(cache_dir.join(CACHE_INDEX_FILE_NAME))
+        .ok()
+        .and_then(|index| serde_json::from_str(&index).ok())
+        .unwrap_or_default()
+}
+
+/// The name of the file some downloaded contents are cached under:
+/// the SHA-256 digest of the contents, keeping the url's extension (if any)
+fn cache_file_name(url: &str, bytes: &[u8]) -> String {
+    let hash = sha256_hex(bytes);
+    let extension = Path::new(url.split(['?', '#']).next().unwrap_or_default())
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .filter(|extension| extension.chars().all(char::is_alphanumeric));
+    match extension {
+        Some(extension) => format!("{}.{}", hash, extension),
+        None => hash,
+    }
+}
+
+/// The current time, in seconds since the unix epoch (or 0 if the clock is set before it).
+fn seconds_since_epoch() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+#[cfg(feature = "network")]
+fn download(url: &str) -> Result<Vec<u8>, BabaError> {
+    let failed = |error: ureq::Error| ModdingError::FetchFailed(url.to_owned(), error.to_string());
+    let mut response = ureq::get(url).call().map_err(failed)?;
+    Ok(response.body_mut().read_to_vec().map_err(failed)?)
+}
+
+#[cfg(not(feature = "network"))]
+fn download(url: &str) -> Result<Vec<u8>, BabaError> {
+    Err(ModdingError::FetchFailed(
+        url.to_owned(),
+        "the program was built without the `network` feature".to_owned(),
+    ))?
+}
@@ -0,0 +1,35 @@
+use std::{env, path::PathBuf};
+
+use super::{GOG_PATH, ITCH_APP_DIR, STEAM_PATH};
+
+/// A launcher (or store) that Baba is You can be installed through.
+///
+/// To support another launcher, add a variant here, list it in [`Launcher::ALL`],
+/// and give the folders it installs to in [`Launcher::candidate_paths`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Launcher {
+    /// Steam
+    Steam,
+    /// The itch.io app
+    Itch,
+    /// GOG Galaxy
+    Gog,
+}
+
+impl Launcher {
+    /// Every supported launcher, in the order [`crate::files::babafiles::BabaFiles::detect`] tries them
+    pub const ALL: [Launcher; 3] = [Launcher::Steam, Launcher::Itch, Launcher::Gog];
+
+    /// The folders the launcher installs Baba is You to by default
+    pub fn candidate_paths(&self) -> Vec<PathBuf> {
+        match self {
+            Launcher::Steam => vec![PathBuf::from(STEAM_PATH)],
+            // the itch app installs games into the user's roaming app data
+            Launcher::Itch => env::var_os("APPDATA")
+                .map(|app_data| PathBuf::from(app_data).join(ITCH_APP_DIR))
+                .into_iter()
+                .collect(),
+            Launcher::Gog => vec![PathBuf::from(GOG_PATH)],
+        }
+    }
+}
@@ -9,8 +9,13 @@ use std::{
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    error::babaerror::BabaError,
-    mods::{baba_function_names, code_to_funcs, luafuncdef::LuaFuncDef, luafunction::LuaFunction},
+    error::{babaerror::BabaError, moddingerror::ModdingError},
+    mods::{
+        code_to_funcs, is_baba_native_name,
+        luafuncdef::LuaFuncDef,
+        luafunction::LuaFunction,
+        luasyntax::{normalize_line_endings, simple_assignment, strip_comments_and_strings},
+    },
 };
 
 use super::writeinto::WriteInto;
@@ -59,7 +64,7 @@ impl LuaFile {
     }
     /// Returns a dictionary of renamed functions (for the purposes of the injection method).
     ///
-    /// The keys are the old names (see [`baba_function_names`]), and the
+    /// The keys are the old names (see [`crate::mods::baba_function_names`]), and the
     /// values are the new names.
     ///
     /// Supports these kinds of syntax (on structure creation):
@@ -87,32 +92,95 @@ impl LuaFile {
             || self.renamed_functions.values().any(|y| *y == func_name)
     }
 
+    /// Combines the renamed functions of this file with those of another
+    /// (see [`LuaFile::renamed_functions`]).
+    ///
+    /// # Errors
+    /// Returns [`ModdingError::RenameError`] if both files rename the same function,
+    /// but to different names (e.g. `local oldinit = init` and `local baseinit = init`).
+    pub fn merge_renamed_functions(
+        &self,
+        other: &LuaFile,
+    ) -> Result<HashMap<String, String>, ModdingError> {
+        let mut result = self.renamed_functions();
+        for (function, rename) in other.renamed_functions() {
+            match result.get(&function) {
+                Some(existing) if *existing != rename => {
+                    return Err(ModdingError::RenameError {
+                        names: vec![existing.clone(), rename],
+                        function,
+                    })
+                }
+                Some(_) => {}
+                None => {
+                    result.insert(function, rename);
+                }
+            }
+        }
+        Ok(result)
+    }
+
     /// Grabs the renamed function for a given definition, if it exists.
     ///
     /// Returns [`None`] if the rename doesn't exist.
     pub fn injection_data(&self, func: &LuaFuncDef) -> Option<String> {
         self.renamed_functions.get(&func.name()).cloned()
     }
+
+    /// Reads and parses every file in a list, keeping the order of the list.
+    /// Files that can't be read (e.g. because they don't exist) are skipped.
+    ///
+    /// With the `parallel` feature, the files are read and parsed in parallel.
+    #[cfg(feature = "parallel")]
+    pub fn read_all(paths: &[PathBuf]) -> Vec<LuaFile> {
+        use rayon::prelude::*;
+
+        paths
+            .par_iter()
+            .filter_map(|path| fs::read_to_string(path).ok())
+            .map(Into::into)
+            .collect()
+    }
+
+    /// Reads and parses every file in a list, keeping the order of the list.
+    /// Files that can't be read (e.g. because they don't exist) are skipped.
+    ///
+    /// With the `parallel` feature, the files are read and parsed in parallel.
+    #[cfg(not(feature = "parallel"))]
+    pub fn read_all(paths: &[PathBuf]) -> Vec<LuaFile> {
+        Self::read_all_sequentially(paths)
+    }
+
+    /// Reads and parses every file in a list one after the other, see [`LuaFile::read_all`].
+    pub fn read_all_sequentially(paths: &[PathBuf]) -> Vec<LuaFile> {
+        paths
+            .iter()
+            .flat_map(fs::read_to_string)
+            .map(Into::into)
+            .collect()
+    }
 }
 
 impl FromStr for LuaFile {
     type Err = Infallible;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // the code is kept with plain line breaks, so that it lines up with its functions
+        let s = &normalize_line_endings(s);
         let functions = code_to_funcs(s);
         // for the renamed functions, they look like this:
         // local new = old
         // new = old
+        // where `old` is exactly the name of a baba function
+        // each line is only read once, and `old` is looked up in the cached set of baba's names,
+        // so this stays quick even for baba's own (very long) files
         let mut renamed_functions = HashMap::new();
-        for line in s.lines() {
-            for name in baba_function_names() {
-                if line.contains(&name) && !line.contains("function") {
-                    // removing the `local`
-                    let line = line.replace("local", "");
-                    let rename = line.split('=').next().unwrap_or("RENAME_NOT_FOUND");
-                    // the replace removes spaces so it's just the name
-                    renamed_functions.insert(name, rename.to_owned().replace(' ', ""));
-                }
+        for line in strip_comments_and_strings(s).lines() {
+            let Some((new, old)) = simple_assignment(line) else {
+                continue;
+            };
+            if is_baba_native_name(old) {
+                renamed_functions.insert(old.to_owned(), new.to_owned());
             }
         }
         Ok(Self {
@@ -138,8 +206,17 @@ impl From<&str> for LuaFile {
 impl TryFrom<PathBuf> for LuaFile {
     type Error = BabaError;
 
+    /// Reads the lua file at the given path.
+    ///
+    /// # Errors
+    /// Errors if the file could not be read, or with [`ModdingError::NonUtf8Lua`]
+    /// if it is not valid UTF-8 (e.g. older mods saved as Latin-1).
     fn try_from(value: PathBuf) -> Result<Self, Self::Error> {
-        let Ok(val) = fs::read_to_string(value)?.parse();
+        let bytes = fs::read(&value).map_err(BabaError::io_at(&value))?;
+        let Ok(code) = String::from_utf8(bytes) else {
+            return Err(ModdingError::NonUtf8Lua(value))?;
+        };
+        let Ok(val) = code.parse();
         Ok(val)
     }
 }
@@ -1,5 +1,17 @@
+use std::{
+    fs,
+    io::{self, Seek, Write},
+    path::{Path, PathBuf},
+};
+
+use zip::{result::ZipError, write::SimpleFileOptions, ZipWriter};
+
+use crate::mods::is_lua_file;
+
 pub mod babafiles;
 pub mod editorfuncs;
+pub mod fetch;
+pub mod launcher;
 pub mod luafile;
 pub mod writeinto;
 
@@ -7,6 +19,10 @@ pub mod writeinto;
 /// This should be located inside of the mod folder (i.e. `Lua\[mod]\[this value]`)
 pub const CONFIG_FILE_NAME: &str = "Config.json";
 
+/// The start of the name of every folder made by [`babafiles::BabaFiles::backup`],
+/// followed by when the backup was made (as a unix timestamp).
+pub const BACKUP_FOLDER_PREFIX: &str = "backup_";
+
 /// A list of "reserved" names that are used by baba.
 /// - `baba`, `museum`, and `new_adv` are used to hold data for the game's three campaigns
 /// - `debug`, while not explicitly used by the game, is typically not shown to the player without modification
@@ -16,8 +32,18 @@ const RESERVED_PACK_NAMES: [&str; 5] = ["baba", "debug", "museum", "new_adv", "l
 /// The steam path to Baba is You, if it was installed via steam
 const STEAM_PATH: &str = r"C:\Program Files (x86)\Steam\steamapps\common\Baba Is You";
 
+/// The path to Baba is You inside of the user's app data folder, if it was installed via the itch.io app
+const ITCH_APP_DIR: &str = r"itch\apps\baba-is-you";
+
+/// The path to Baba is You, if it was installed via GOG Galaxy (into its default library)
+const GOG_PATH: &str = r"C:\Program Files (x86)\GOG Galaxy\Games\Baba Is You";
+
+/// How many of [`BABA_LUA_FILE_NAMES`] need to be present for a folder to count as an installation of baba,
+/// see [`babafiles::BabaFiles::from_raw_checked`]
+pub const MIN_NATIVE_FILES: usize = 3;
+
 /// The names of all the baba files that contain overridable code.
-const BABA_LUA_FILE_NAMES: [&str; 27] = [
+pub const BABA_LUA_FILE_NAMES: [&str; 27] = [
     "blocks",
     "changes",
     "clears",
@@ -46,3 +72,103 @@ const BABA_LUA_FILE_NAMES: [&str; 27] = [
     "values",
     "vision",
 ];
+
+/// Returns the name of the native baba file that the path points to, if it is one.
+///
+/// For example, `[...]\Lua\movement.lua` returns `Some("movement")`,
+/// whereas `[...]\Lua\my_mod.lua` returns [`None`].
+pub fn native_file_stem(path: &Path) -> Option<String> {
+    if !is_lua_file(path) {
+        return None;
+    }
+    let stem = path.file_stem()?.to_str()?;
+    BABA_LUA_FILE_NAMES.contains(&stem).then(|| stem.to_owned())
+}
+
+/// Finds the config file inside of a mod folder, whatever the case of its name
+/// (mods are shipped with `Config.json`, `config.json`, `CONFIG.JSON` and so on).
+///
+/// An exact match for [`CONFIG_FILE_NAME`] is preferred over any other spelling.
+pub fn find_config_file(dir: &Path) -> Option<PathBuf> {
+    let exact = dir.join(CONFIG_FILE_NAME);
+    if exact.is_file() {
+        return Some(exact);
+    }
+    dir.read_dir()
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| is_config_file_name(path) && path.is_file())
+}
+
+/// Returns whether a path is named like a config file, ignoring case (see [`CONFIG_FILE_NAME`])
+pub fn is_config_file_name(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.eq_ignore_ascii_case(CONFIG_FILE_NAME))
+}
+
+/// The path a file is first written to by [`write_atomically`], before being moved into place.
+///
+/// This is a hidden file next to the original, e.g. `Config.json` is written as `.Config.json.tmp`.
+pub fn temporary_path(path: &Path) -> PathBuf {
+    let mut name = std::ffi::OsString::from(".");
+    name.push(path.file_name().unwrap_or_default());
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+/// Writes a file so that it is either fully written, or not touched at all.
+///
+/// The contents are written to a [`temporary_path`] first, which is then renamed over the file.
+/// If anything goes wrong along the way, the original file is left as it was.
+pub fn write_atomically(path: &Path, contents: impl AsRef<[u8]>) -> Result<(), io::Error> {
+    let temporary = temporary_path(path);
+    let result = fs::write(&temporary, contents).and_then(|_| fs::rename(&temporary, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temporary);
+    }
+    result
+}
+
+/// Copies a file or a directory (and everything inside of it) from one place to another.
+///
+/// Any missing parent directories of the destination are created.
+pub fn copy_recursively(from: &Path, to: &Path) -> Result<(), io::Error> {
+    if from.is_dir() {
+        fs::create_dir_all(to)?;
+        for entry in from.read_dir()? {
+            let entry = entry?;
+            copy_recursively(&entry.path(), &to.join(entry.file_name()))?;
+        }
+    } else {
+        if let Some(parent) = to.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(from, to)?;
+    }
+    Ok(())
+}
+
+/// Adds a file or a directory (and everything inside of it) to a `.zip` archive, under the given name.
+///
+/// Names inside of the archive are always separated with `/`, e.g. `my_mod/code.lua`.
+pub fn zip_recursively<W: Write + Seek>(
+    writer: &mut ZipWriter<W>,
+    from: &Path,
+    name: &str,
+    options: SimpleFileOptions,
+) -> Result<(), ZipError> {
+    if from.is_dir() {
+        writer.add_directory(name, options)?;
+        for entry in from.read_dir()? {
+            let entry = entry?;
+            let child = format!("{}/{}", name, entry.file_name().to_string_lossy());
+            zip_recursively(writer, &entry.path(), &child, options)?;
+        }
+    } else {
+        writer.start_file(name, options)?;
+        io::copy(&mut fs::File::open(from)?, writer)?;
+    }
+    Ok(())
+}
@@ -2,7 +2,12 @@ use std::{fs, io, path::Path};
 
 use serde_json::Value;
 
+use super::write_atomically;
+
 /// Writes a structure into a file.
+///
+/// Files are written atomically (see [`write_atomically`]), so a failed write never leaves
+/// a half-written file behind.
 pub trait WriteInto {
     /// The desired file name for the structure.
     /// You can use [`WriteInto::write_into_as_is`] if you want to
@@ -19,15 +24,20 @@ pub trait WriteInto {
     /// Writes the contents of the structure into a file, ignoring the desired filename.
     fn write_into_as_is(&self, path: &Path) -> Result<(), io::Error> {
         let this_file = self.as_file();
-        fs::write(path, this_file)
+        write_atomically(path, this_file)
     }
 
     /// Writes the contents of the structure into a file, using a custom filename.
+    ///
+    /// Any folders leading up to the file that don't exist yet are created.
     fn write_into_using(&self, path: &Path, name: &str) -> Result<(), io::Error> {
         let this_file = self.as_file();
         let mut pathbuf = path.to_owned();
         pathbuf.push(name);
-        fs::write(pathbuf, this_file)
+        if let Some(parent) = pathbuf.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        write_atomically(&pathbuf, this_file)
     }
 }
 
@@ -0,0 +1,68 @@
+use std::{fs, path::Path};
+
+use crate::error::babaerror::BabaError;
+
+use super::fetch_field;
+
+/// Represents a single level inside of a levelpack.
+///
+/// A level is made up of a `.ld` file (holding its metadata) and a `.l` file (holding the level itself),
+/// both named after the level's id (e.g. `1level.ld` and `1level.l`).
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct LevelEntry {
+    /// The level's id (the name of its files, without the extension)
+    id: String,
+    /// The name of the level
+    name: String,
+    /// The author of the level
+    author: String,
+    /// Whether or not the level's `.l` file exists next to its `.ld` file
+    has_level_file: bool,
+}
+
+impl LevelEntry {
+    /// Reads a level from its `.ld` file.
+    ///
+    /// # Errors
+    /// This function may error if the `.ld` file could not be read ([`std::io::Error`])
+    pub fn new(ld_path: &Path) -> Result<Self, BabaError> {
+        let metadata = fs::read_to_string(ld_path)?;
+        let mut this = Self {
+            id: ld_path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            has_level_file: ld_path.with_extension("l").is_file(),
+            ..Default::default()
+        };
+        for line in metadata.lines() {
+            if let Ok(name) = fetch_field("name", line) {
+                this.name = name;
+            }
+            if let Ok(author) = fetch_field("author", line) {
+                this.author = author;
+            }
+        }
+        Ok(this)
+    }
+
+    /// Gets the level's id
+    pub fn id(&self) -> String {
+        self.id.clone()
+    }
+
+    /// Gets the name of the level
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// Gets the author of the level
+    pub fn author(&self) -> String {
+        self.author.clone()
+    }
+
+    /// Gets whether or not the level's `.l` file exists
+    pub fn has_level_file(&self) -> bool {
+        self.has_level_file
+    }
+}
@@ -3,7 +3,10 @@ use std::str::FromStr;
 use crate::error::levelpackerror::LevelpackError;
 
 /// Represents a file inside the levelpack folder
-#[derive(Debug)]
+///
+/// Parsing is case-insensitive, and accepts the file's (or folder's) full name,
+/// e.g. `"Music"` or `"world_data.txt"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LevelpackFile {
     /// The world data file (`world_data.txt`)
     WorldDataTxt,
@@ -23,6 +26,20 @@ pub enum LevelpackFile {
     Themes,
 }
 
+impl LevelpackFile {
+    /// Every file (and folder) a levelpack may have
+    pub const ALL: [LevelpackFile; 8] = [
+        LevelpackFile::WorldDataTxt,
+        LevelpackFile::IconPng,
+        LevelpackFile::Images,
+        LevelpackFile::Lua,
+        LevelpackFile::Music,
+        LevelpackFile::Palettes,
+        LevelpackFile::Sprites,
+        LevelpackFile::Themes,
+    ];
+}
+
 impl FromStr for LevelpackFile {
     type Err = LevelpackError;
 
@@ -31,6 +48,7 @@ impl FromStr for LevelpackFile {
             "worlddata" | "world_data.txt" => Self::WorldDataTxt,
             "images" => Self::Images,
             "lua" | "mods" => Self::Lua,
+            "music" => Self::Music,
             "palettes" => Self::Palettes,
             "sprites" => Self::Sprites,
             "themes" => Self::Themes,
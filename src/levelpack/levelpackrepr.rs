@@ -1,14 +1,28 @@
-use std::{fmt::Display, fs, path::PathBuf};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Display,
+    fs,
+    path::PathBuf,
+};
+
+#[cfg(feature = "gui")]
+use egui::ColorImage;
 
+#[cfg(feature = "gui")]
+use crate::application::load_image_from_path;
 use crate::{
-    error::{babaerror::BabaError, levelpackerror::LevelpackError},
-    mods::babamod::BabaMod,
+    error::{babaerror::BabaError, levelpackerror::LevelpackError, moddingerror::ModdingError},
+    files::{babafiles::BabaFiles, copy_recursively, write_atomically, writeinto::WriteInto},
+    mods::{babamod::BabaMod, luafunction::LuaFunction, mods_in_directory},
 };
 
-use super::{fetch_field, levelpackfile::LevelpackFile, WORLD_DATA_FILE_NAME};
+use super::{
+    fetch_field, levelentry::LevelEntry, levelpackfile::LevelpackFile, set_field, GENERAL_SECTION,
+    LEVEL_DATA_EXTENSION, WORLD_DATA_FILE_NAME,
+};
 
 /// Represents a single levelpack in Baba is you.
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub struct LevelpackRepr {
     /// The path to the levelpack (absolute)
     path: PathBuf,
@@ -42,7 +56,9 @@ impl LevelpackRepr {
         }
 
         // load the world_data.txt into a String
-        let world_data = fs::read_to_string(path.join(WORLD_DATA_FILE_NAME))?;
+        let world_data_path = path.join(WORLD_DATA_FILE_NAME);
+        let world_data =
+            fs::read_to_string(&world_data_path).map_err(BabaError::io_at(world_data_path))?;
 
         // Initialize the Levelpack with dummy data
         let mut this = Self {
@@ -84,7 +100,7 @@ impl LevelpackRepr {
         Ok(this)
     }
 
-    /// Attempts to find the set of mods in the levelpack.
+    /// Attempts to find the set of mods in the levelpack (see [`mods_in_directory`]).
     /// This may be zero.
     ///
     /// # Errors
@@ -94,24 +110,304 @@ impl LevelpackRepr {
         if !self.mods_enabled {
             return Ok(vec![]);
         }
-        let lua_path = self.pack_file(LevelpackFile::Lua);
-        let path_iter = lua_path.read_dir()?;
-        // create a list of levelpacks
+        mods_in_directory(&self.pack_file(LevelpackFile::Lua))
+    }
+
+    /// Checks the pack's mods for anything that would stop them from working together,
+    /// reporting every problem found rather than stopping at the first.
+    ///
+    /// This looks for mods sharing an init file ([`ModdingError::DuplicateInit`]),
+    /// a mod ID ([`ModdingError::DuplicateModId`]), or a declared sprite ([`ModdingError::SpriteCollision`]).
+    /// If the pack's mods can't be read, there is nothing to check, so nothing is reported.
+    pub fn validate_mods(&self) -> Vec<ModdingError> {
+        let mods = self.mods().unwrap_or_default();
+        let mut inits: BTreeMap<PathBuf, Vec<String>> = BTreeMap::new();
+        let mut ids: BTreeMap<String, usize> = BTreeMap::new();
+        let mut sprites: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for baba_mod in &mods {
+            let id = baba_mod.mod_id();
+            if let Some(init) = baba_mod.init_path() {
+                inits.entry(init).or_default().push(id.clone());
+            }
+            for sprite in baba_mod.defined_sprites() {
+                sprites.entry(sprite).or_default().push(id.clone());
+            }
+            *ids.entry(id).or_default() += 1;
+        }
+        let duplicate_inits =
+            inits
+                .into_iter()
+                .filter(|(_, mods)| mods.len() > 1)
+                .map(|(init, mut mods)| {
+                    mods.sort();
+                    ModdingError::DuplicateInit(init, mods)
+                });
+        let duplicate_ids = ids
+            .into_iter()
+            .filter(|(_, count)| *count > 1)
+            .map(|(id, _)| ModdingError::DuplicateModId(id));
+        let sprite_collisions =
+            sprites
+                .into_iter()
+                .filter(|(_, mods)| mods.len() > 1)
+                .map(|(sprite, mut mods)| {
+                    mods.sort();
+                    ModdingError::SpriteCollision(sprite, mods)
+                });
+        duplicate_inits
+            .chain(duplicate_ids)
+            .chain(sprite_collisions)
+            .collect()
+    }
+
+    /// Works out which of the pack's mods rely on each other, mapping each mod ID
+    /// to the IDs of the other mods whose functions it calls (see [`LuaFunction::calls`]).
+    ///
+    /// Calls to baba's own functions don't count, even if another mod overrides them,
+    /// since those would be there without the other mod. Mods whose code can't be read
+    /// simply don't define or call anything, and if the pack's mods can't be read, the graph is empty.
+    pub fn dependency_graph(&self) -> HashMap<String, HashSet<String>> {
+        let mods = self.mods().unwrap_or_default();
+        // the (non-native) functions each mod defines, by name
+        let defined: Vec<(String, HashSet<String>)> = mods
+            .iter()
+            .map(|baba_mod| {
+                let names = baba_mod
+                    .defined_function_definitions()
+                    .into_iter()
+                    .filter(|definition| !definition.is_baba_native())
+                    .map(|definition| definition.name())
+                    .collect();
+                (baba_mod.mod_id(), names)
+            })
+            .collect();
+        let mut graph = HashMap::new();
+        for baba_mod in &mods {
+            let id = baba_mod.mod_id();
+            let calls: HashSet<String> = baba_mod
+                .defined_functions()
+                .iter()
+                .flat_map(LuaFunction::calls)
+                .collect();
+            let dependencies = defined
+                .iter()
+                .filter(|(other, names)| *other != id && !names.is_disjoint(&calls))
+                .map(|(other, _)| other.clone())
+                .collect();
+            graph.insert(id, dependencies);
+        }
+        graph
+    }
+
+    /// Gets the path to the levelpack's folder
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    /// Gets the name of the levelpack
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// Gets the author of the levelpack
+    pub fn author(&self) -> String {
+        self.author.clone()
+    }
+
+    /// Gets the required amount of Spores for 100%
+    pub fn prize_max(&self) -> usize {
+        self.prize_max
+    }
+
+    /// Gets the required amount of World Map Clears for 100%
+    pub fn clear_max(&self) -> usize {
+        self.clear_max
+    }
+
+    /// Gets the required amount of Bonuses for 100%
+    pub fn bonus_max(&self) -> usize {
+        self.bonus_max
+    }
+
+    /// Gets whether or not mods are enabled for the levelpack
+    pub fn mods_enabled(&self) -> bool {
+        self.mods_enabled
+    }
+
+    /// Installs a mod into the levelpack.
+    ///
+    /// The mod itself (and its init file) is copied into the pack's `Lua` folder,
+    /// its sprites are copied into the pack's `Sprites` folder, and mods are enabled
+    /// in the pack's `world_data.txt`.
+    ///
+    /// Global mods (see [`BabaMod::is_global`]) are instead copied into the game-wide folders
+    /// ([`BabaFiles::global_mods_dir`] and [`BabaFiles::global_sprites_dir`]), and the pack is left as it is.
+    ///
+    /// Before anything is copied, the mod is checked against every mod already in the pack
+    /// (and every global mod), via [`BabaMod::is_compatible_with`].
+    ///
+    /// # Errors
+    /// This function may error if:
+    /// - The mod is incompatible with at least one installed mod ([`ModdingError::InstallConflict`])
+    /// - There was an issue reading the installed mods, copying the files, or updating `world_data.txt` ([`std::io::Error`])
+    pub fn install_mod(&self, baba_mod: &BabaMod, files: &BabaFiles) -> Result<(), BabaError> {
+        let (lua, sprites) = match baba_mod.is_global() {
+            true => (files.global_mods_dir(), files.global_sprites_dir()),
+            false => (
+                self.pack_file(LevelpackFile::Lua),
+                self.pack_file(LevelpackFile::Sprites),
+            ),
+        };
+
+        // check for conflicts before touching anything
+        let pack_lua = self.pack_file(LevelpackFile::Lua);
+        let installed = match pack_lua.exists() {
+            true => mods_in_directory(&pack_lua)?,
+            false => vec![],
+        };
+        let global = match files.global_mods_dir().exists() {
+            true => files.global_mods()?,
+            false => vec![],
+        };
+        let clashes: Vec<String> = installed
+            .iter()
+            .chain(global.iter())
+            .filter(|other| other.path() != baba_mod.path())
+            .filter(|other| !baba_mod.is_compatible_with(other))
+            .map(BabaMod::mod_id)
+            .collect();
+        if !clashes.is_empty() {
+            return Err(BabaError::Modding(ModdingError::InstallConflict(clashes)));
+        }
+
+        // copy over the mod's files
+        fs::create_dir_all(&lua)?;
+        fs::create_dir_all(&sprites)?;
+        let mod_sprites = baba_mod.sprites_folder().ok();
+        for path in baba_mod.all_relevant_files()? {
+            // anything inside of the mod's folder is copied along with it
+            if path != baba_mod.path() && path.starts_with(baba_mod.path()) {
+                continue;
+            }
+            if !path.exists() {
+                continue;
+            }
+            let Some(file_name) = path.file_name() else {
+                continue;
+            };
+            let destination = match &mod_sprites {
+                Some(folder) if path.starts_with(folder) => sprites.join(file_name),
+                _ => lua.join(file_name),
+            };
+            copy_recursively(&path, &destination)?;
+        }
+        if let Some(init) = baba_mod.init_path() {
+            if let Some(file_name) = init.file_name().filter(|_| init.is_file()) {
+                fs::copy(&init, lua.join(file_name))?;
+            }
+        }
+
+        // and finally, turn mods on for the pack (global mods are on everywhere already)
+        if baba_mod.is_global() {
+            return Ok(());
+        }
+        let world_data_path = self.path.join(WORLD_DATA_FILE_NAME);
+        let world_data = fs::read_to_string(&world_data_path)?;
+        write_atomically(&world_data_path, set_field(&world_data, "mods", "1"))?;
+        Ok(())
+    }
+
+    /// Uninstalls a mod from the levelpack, removing only the files it owns.
+    ///
+    /// This removes the mod itself, its init file, and the sprites it declares.
+    /// Sprites that are also claimed by another mod in the pack are left alone.
+    ///
+    /// # Errors
+    /// This function may error if:
+    /// - The mod does not live inside of this pack's `Lua` folder ([`ModdingError::NotInLevelpack`])
+    /// - There was an issue removing the files ([`std::io::Error`])
+    pub fn uninstall_mod(&self, baba_mod: &BabaMod) -> Result<(), BabaError> {
+        let lua = self.pack_file(LevelpackFile::Lua);
+        if !baba_mod.path().starts_with(&lua) {
+            return Err(BabaError::Modding(ModdingError::NotInLevelpack(
+                baba_mod.path().to_owned(),
+            )));
+        }
+
+        // sprites claimed by other mods are kept around
+        let shared: HashSet<PathBuf> = mods_in_directory(&lua)?
+            .iter()
+            .filter(|other| other.path() != baba_mod.path())
+            .flat_map(|other| other.sprite_files().unwrap_or_default())
+            .collect();
+        for sprite in baba_mod.sprite_files().unwrap_or_default() {
+            if !shared.contains(&sprite) {
+                fs::remove_file(sprite)?;
+            }
+        }
+        if let Some(init) = baba_mod.init_path().filter(|init| init.is_file()) {
+            fs::remove_file(init)?;
+        }
+        if baba_mod.path().is_dir() {
+            fs::remove_dir_all(baba_mod.path())?;
+        } else if baba_mod.path().exists() {
+            fs::remove_file(baba_mod.path())?;
+        }
+        Ok(())
+    }
+
+    /// Loads the levelpack's icon (`icon.png`) as an image.
+    ///
+    /// # Errors
+    /// This function may error if:
+    /// - The levelpack has no icon ([`LevelpackError::IconNotFound`])
+    /// - The icon could not be decoded as an image
+    #[cfg(feature = "gui")]
+    pub fn icon(&self) -> Result<ColorImage, BabaError> {
+        let path = self.pack_file(LevelpackFile::IconPng);
+        if !path.is_file() {
+            return Err(BabaError::Levelpack(LevelpackError::IconNotFound(path)));
+        }
+        Ok(load_image_from_path(&path)?)
+    }
+
+    /// Finds every level in the levelpack, by looking for `.ld` files in the pack's folder.
+    /// Levels are sorted by their id.
+    ///
+    /// # Errors
+    /// This function may error if the pack's folder, or any of the `.ld` files, could not be read ([`std::io::Error`])
+    pub fn levels(&self) -> Result<Vec<LevelEntry>, BabaError> {
         let mut result = Vec::new();
+        for entry in self.path.read_dir()? {
+            let path = entry?.path();
+            if path.is_file()
+                && path.extension().and_then(|extension| extension.to_str())
+                    == Some(LEVEL_DATA_EXTENSION)
+            {
+                result.push(LevelEntry::new(&path)?);
+            }
+        }
+        result.sort_by_key(LevelEntry::id);
+        Ok(result)
+    }
 
-        // before we iterate over the entries, check to see if any actually exist
-        let iter = path_iter.flatten().collect::<Vec<_>>();
-        // if not, just return an empty vector
-        if iter.is_empty() {
+    /// Lists the entries of one of the levelpack's folders (such as its music or sprites), sorted by path.
+    ///
+    /// A pack without the folder simply has nothing in it, so this returns an empty list.
+    ///
+    /// # Errors
+    /// This function may error if the folder could not be read ([`std::io::Error`]),
+    /// which includes `kind` being a file rather than a folder.
+    pub fn files_in(&self, kind: LevelpackFile) -> Result<Vec<PathBuf>, BabaError> {
+        let folder = self.pack_file(kind);
+        if !folder.exists() {
             return Ok(vec![]);
         }
-        // iterate over each entry
-        for entry in iter {
-            // create a BabaMod from the entry
-            let baba_mod = BabaMod::new(entry.path());
-            // push it onto the list
-            result.push(baba_mod);
+        let mut result = Vec::new();
+        for entry in folder.read_dir()? {
+            result.push(entry?.path());
         }
+        result.sort();
         Ok(result)
     }
 
@@ -123,6 +419,35 @@ impl LevelpackRepr {
     }
 }
 
+impl WriteInto for LevelpackRepr {
+    const FILE_NAME: &str = WORLD_DATA_FILE_NAME;
+
+    /// Regenerates the pack's `world_data.txt`.
+    ///
+    /// The fields held by the levelpack are written over the pack's current `world_data.txt`,
+    /// so everything else in it (levels, sections other than [`GENERAL_SECTION`], etc.) is kept.
+    fn as_file(&self) -> String {
+        let existing = fs::read_to_string(self.path.join(WORLD_DATA_FILE_NAME))
+            .unwrap_or_else(|_| format!("{}\n", GENERAL_SECTION));
+        let mods = match self.mods_enabled {
+            true => "1",
+            false => "0",
+        };
+        [
+            ("name", self.name.clone()),
+            ("author", self.author.clone()),
+            ("prize_max", self.prize_max.to_string()),
+            ("clear_max", self.clear_max.to_string()),
+            ("bonus_max", self.bonus_max.to_string()),
+            ("mods", mods.to_owned()),
+        ]
+        .iter()
+        .fold(existing, |data, (field, value)| {
+            set_field(&data, field, value)
+        })
+    }
+}
+
 impl Display for LevelpackRepr {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -2,16 +2,23 @@ use std::str::FromStr;
 
 use crate::error::levelpackerror::LevelpackError;
 
+pub mod levelentry;
 pub mod levelpackrepr;
 pub mod levelpackfile;
 
 /// The name of the file that holds the world data
 pub const WORLD_DATA_FILE_NAME: &str = "world_data.txt";
 
+/// The extension of the files that hold a level's metadata (name, author, etc.)
+pub const LEVEL_DATA_EXTENSION: &str = "ld";
+
+/// The header of the section in `world_data.txt` that holds the pack's general data
+pub const GENERAL_SECTION: &str = "[general]";
+
 /// Attempts to get and parse a field from a line of text.
 ///
 /// For example: giving `fetch_field<usize>("name", "name=abc")`
-/// should return `Ok("abc")`
+/// should return `Ok("abc")`, as should `"name = abc"`, since whitespace around the key and value is ignored.
 ///
 /// # Errors
 /// This function may error if:
@@ -25,12 +32,58 @@ where
     let split = data
         .split_once('=')
         .ok_or(LevelpackError::FieldParsingError(true))?;
-    if split.0 != field {
+    // hand-edited files sometimes space out the `=`
+    if split.0.trim() != field {
         Err(LevelpackError::FieldParsingError(false))
     } else {
         split
             .1
+            .trim()
             .parse()
             .map_err(|_| LevelpackError::StringParsingError("Malformed world_data.txt".to_owned()))
     }
 }
+
+/// Attempts to get and parse every instance of a field from a block of text,
+/// for fields that may be repeated (such as a pack's level list).
+///
+/// Each line is read as with [`fetch_field`]; lines that don't hold the field,
+/// or whose value can't be parsed, are skipped.
+pub fn fetch_all_fields<T>(field: &str, data: &str) -> Vec<T>
+where
+    T: FromStr,
+{
+    data.lines()
+        .filter_map(|line| fetch_field(field, line).ok())
+        .collect()
+}
+
+/// Sets a field inside of a block of text (such as `world_data.txt`), returning the new text.
+///
+/// The first line holding the field is replaced with `"field=value"`. If no such line exists,
+/// one is added after the [`GENERAL_SECTION`] header (or at the end, if there's no header).
+pub fn set_field(data: &str, field: &str, value: &str) -> String {
+    let new_line = format!("{field}={value}");
+    let mut found = false;
+    let mut lines: Vec<String> = data
+        .lines()
+        .map(|line| {
+            if !found && fetch_field::<String>(field, line).is_ok() {
+                found = true;
+                new_line.clone()
+            } else {
+                line.to_owned()
+            }
+        })
+        .collect();
+    if !found {
+        let position = lines
+            .iter()
+            .position(|line| line.trim() == GENERAL_SECTION)
+            .map_or(lines.len(), |index| index + 1);
+        lines.insert(position, new_line);
+    }
+    let mut result = lines.join("\n");
+    result.push('\n');
+    result
+}
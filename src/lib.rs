@@ -0,0 +1,19 @@
+//! See the readme for more information on this crate.
+//!
+//! The library holds everything needed to work with baba's files without a window:
+//! finding an installation ([`files`]), reading mods ([`mods`]), merging them ([`merge`]),
+//! and inspecting levelpacks ([`levelpack`]).
+//! Some of this can also be done from the command line, see [`command`].
+//! The graphical application lives in [`application`], behind the (default) `gui` feature.
+
+#![allow(dead_code)]
+
+#[cfg(feature = "gui")]
+pub mod application;
+pub mod command;
+pub mod error;
+pub mod files;
+pub mod levelpack;
+pub mod merge;
+pub mod mods;
+mod test;
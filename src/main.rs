@@ -1,26 +1,42 @@
 //! See the readme for more information on this crate.
-//! It is not intended to be used as a library.
+//! This is the graphical application; the logic behind it is available as a library.
 
-#![allow(dead_code)]
+use std::{env, process::exit};
 
-use application::{app::App, icon};
-use error::babaerror::BabaError;
-
-pub mod application;
-pub mod error;
-pub mod files;
-pub mod levelpack;
-pub mod merge;
-pub mod mods;
-mod test;
+use babamodmanager::{
+    application::{app::App, find_data_dir, icon},
+    command::{Command, USAGE},
+    error::babaerror::BabaError,
+};
 
 /// The name used by the window.
 /// If you've forked this repository, you can change this to indicate so.
 const APP_NAME: &str = "Baba Mod Manager";
 
 fn main() -> Result<(), BabaError> {
+    // commands given on the command line are run without opening a window
+    match Command::from_args(env::args().skip(1)) {
+        Ok(Some(command)) => match command.run() {
+            Ok(()) => exit(0),
+            Err(error) => {
+                eprintln!("{}", error);
+                exit(1)
+            }
+        },
+        Ok(None) => {}
+        Err(error) => {
+            eprintln!("{}\n{}", error, USAGE);
+            exit(2)
+        }
+    }
+
     let mut native_options = eframe::NativeOptions::default();
     native_options.viewport = native_options.viewport.with_icon(icon()?);
-    eframe::run_native(APP_NAME, native_options, Box::new(|cc| Ok(Box::new(App::new(cc)))))?;
+    let data_dir = find_data_dir(APP_NAME);
+    eframe::run_native(
+        APP_NAME,
+        native_options,
+        Box::new(|cc| Ok(Box::new(App::new(cc, data_dir)))),
+    )?;
     Ok(())
 }
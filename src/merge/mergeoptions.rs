@@ -2,6 +2,8 @@ use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
 
+use super::{LEFT_HAND_SUFFIX, MERGED_FILE_NAME, RIGHT_HAND_SUFFIX};
+
 /// A set of options to be configured when merging two mods.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MergeOptions {
@@ -10,5 +12,48 @@ pub struct MergeOptions {
     /// Where to drop off the merged code
     pub location: PathBuf,
     /// the name of the lua file to be deposited
-    pub file_name: String
+    pub file_name: String,
+    /// Whether or not to replace a mod already at the location.
+    /// If this is false, merging into an existing mod's folder errors instead.
+    #[serde(default)]
+    pub overwrite: bool,
+    /// What to add onto the names of functions that clash between the two mods
+    #[serde(default)]
+    pub suffixes: RenameSuffixes,
+    /// The order (by mod ID) to merge mods in with [`super::merge_many`], earliest first.
+    /// Mods that aren't listed come after the listed ones, in the order they were given.
+    #[serde(default)]
+    pub priority: Vec<String>,
+}
+
+impl Default for MergeOptions {
+    fn default() -> Self {
+        Self {
+            include_init: true,
+            location: PathBuf::from("."),
+            file_name: MERGED_FILE_NAME.to_owned(),
+            overwrite: false,
+            suffixes: RenameSuffixes::default(),
+            priority: Vec::new(),
+        }
+    }
+}
+
+/// The suffixes added onto functions (that aren't native to baba) defined by both mods when merging.
+/// Each mod's version of the function is renamed, so both can be kept.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenameSuffixes {
+    /// The suffix for the function from the left (first) mod
+    pub left: String,
+    /// The suffix for the function from the right (second) mod
+    pub right: String,
+}
+
+impl Default for RenameSuffixes {
+    fn default() -> Self {
+        Self {
+            left: LEFT_HAND_SUFFIX.to_owned(),
+            right: RIGHT_HAND_SUFFIX.to_owned(),
+        }
+    }
 }
\ No newline at end of file
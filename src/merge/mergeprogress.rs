@@ -0,0 +1,21 @@
+/// How far along a merge is, handed to the callback of [`super::merge_many_with_progress`]
+/// after each step, e.g. to drive a progress bar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeProgress {
+    /// How many steps have been finished (starting from 1)
+    pub current: usize,
+    /// How many steps there are in total
+    pub total: usize,
+    /// What was just merged (the ID of the mod the step belongs to)
+    pub label: String,
+}
+
+impl MergeProgress {
+    /// How far along the merge is, from `0.0` to `1.0`
+    pub fn fraction(&self) -> f32 {
+        match self.total {
+            0 => 1.0,
+            total => self.current as f32 / total as f32,
+        }
+    }
+}
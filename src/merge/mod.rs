@@ -1,22 +1,44 @@
 pub mod mergeoptions;
+pub mod mergeprogress;
+
+use std::collections::HashSet;
 
 use diff_match_patch_rs::{DiffMatchPatch, PatchInput};
-use mergeoptions::MergeOptions;
+use mergeoptions::{MergeOptions, RenameSuffixes};
+use mergeprogress::MergeProgress;
 
 use crate::{
     error::{babaerror::BabaError, moddingerror::ModdingError},
     files::luafile::LuaFile,
-    mods::{babamod::BabaMod, concat_strings, config::Config, luafunction::LuaFunction},
+    mods::{
+        babamod::BabaMod,
+        clean_author_names,
+        compatibility::OverlapKind,
+        concat_strings,
+        config::Config,
+        luafunction::LuaFunction,
+        luasyntax::{called_names, replace_identifier, validate_structure},
+    },
 };
 
 /// Defines the prefix of a lua function,
 /// if duplicates are found, and it is
 /// on the *left* hand side of the arguments
-const LEFT_HAND_SUFFIX: &str = "_left";
+pub const LEFT_HAND_SUFFIX: &str = "_left";
 /// Defines the prefix of a lua function,
 /// if duplicates are found, and it is
 /// on the *right* hand side of the arguments
-const RIGHT_HAND_SUFFIX: &str = "_right";
+pub const RIGHT_HAND_SUFFIX: &str = "_right";
+
+/// The default name of the lua file holding the merged code,
+/// see [`MergeOptions::file_name`]
+pub const MERGED_FILE_NAME: &str = "merged.lua";
+
+/// Placed between the IDs of merged mods, see [`merged_mod_id`]
+pub const MERGED_ID_SEPARATOR: &str = "_x_";
+
+/// The longest a merged mod's ID can be, see [`merged_mod_id`]
+pub const MAX_MERGED_ID_LENGTH: usize = 64;
 
 /// The mode used by [`DiffMatchPatch`].
 /// This can be one of two types:
@@ -29,12 +51,15 @@ type DiffMode = diff_match_patch_rs::Compat;
 /// Attempts to merge two [`LuaFile`]s.
 /// # Semantics
 /// - The order of parameters matter - the two files are merged into one, with the left parameter coming first, and the second parameter coming after. In other words, the left parameter has priority.
-/// - Functions are only merged if they both override a function from Baba is You. Otherwise, they are renamed with additional suffixes - see [`LEFT_HAND_SUFFIX`] and [`RIGHT_HAND_SUFFIX`] for specifics on those values.
+/// - Functions are only merged if they both override a function from Baba is You. Otherwise, they are renamed with additional suffixes - see [`LEFT_HAND_SUFFIX`] and [`RIGHT_HAND_SUFFIX`] for specifics on those values (or [`merge_files_with`] to use other suffixes).
+/// - If a renamed function would clash with a function that already exists, a number is added onto the end of it (e.g. `foo_left2`).
 /// - In the case where functions are merged, the file is ordered with the left file's data first, then merged data, then the right file's data.
 /// # Errors
 /// This function will only error if merging is not possible in some way, shape, or form.
 /// Specifics:
 /// - Will return [`ModdingError::RenameError`] if, while attempting to merge an Injected and Overridden mod (see below), the dictionary of renamed variables was not properly set in the mod with the injected function.
+/// - Will also return [`ModdingError::RenameError`] if both files rename the same baba function to different names (see [`LuaFile::merge_renamed_functions`]).
+/// - Will return [`ModdingError::MergeProducedInvalidLua`] if the merged code is not properly structured (see [`validate_structure`]).
 /// - Will return [`crate::error::BabaError::Dmp`] as per the specifications of [`merge_override_functions`] or [`merge_injected_functions`], depending on whether both mods use the Override or Injection method.
 /// ## Override vs Injection
 /// When it comes to baba modding, there are two ways to replace a function native to baba.
@@ -79,6 +104,25 @@ pub fn merge_files(
     right_file: LuaFile,
     baba_funcs: &[LuaFunction],
 ) -> Result<LuaFile, BabaError> {
+    merge_files_with(
+        left_file,
+        right_file,
+        baba_funcs,
+        &RenameSuffixes::default(),
+    )
+}
+
+/// Attempts to merge two [`LuaFile`]s, renaming clashing functions with the given suffixes.
+///
+/// See [`merge_files`] for the semantics of merging.
+pub fn merge_files_with(
+    left_file: LuaFile,
+    right_file: LuaFile,
+    baba_funcs: &[LuaFunction],
+    suffixes: &RenameSuffixes,
+) -> Result<LuaFile, BabaError> {
+    // combine the injected functions of both files, making sure they agree with each other
+    let renamed_functions = left_file.merge_renamed_functions(&right_file)?;
     let mut left = left_file.code();
     let mut right = right_file.code();
     let mut merged = String::new();
@@ -86,6 +130,9 @@ pub fn merge_files(
     let lhs = left_file.definitions();
     let rhs = right_file.definitions();
 
+    // every name in use, so renamed functions don't clash with existing ones
+    let mut taken: HashSet<String> = lhs.union(&rhs).map(|func| func.name()).collect();
+
     // grab the intersections
     let intersections = lhs.intersection(&rhs);
     // iterate over the intersections
@@ -93,18 +140,16 @@ pub fn merge_files(
     // and we just return the two files concatenated (see below)
     for func in intersections {
         // if it is not native to baba...
-        if !func.is_baba_native() {
+        if OverlapKind::of(func) == OverlapKind::Conflict {
             // we can just rename the functions
             // grab its name
             let name = func.name();
             // create new names for the left and right hand sides
-            let mut left_func = name.clone();
-            left_func.push_str(LEFT_HAND_SUFFIX);
-            let mut right_func = name.clone();
-            right_func.push_str(RIGHT_HAND_SUFFIX);
+            let left_func = unused_name(format!("{}{}", name, suffixes.left), &mut taken);
+            let right_func = unused_name(format!("{}{}", name, suffixes.right), &mut taken);
             // replace each instance of the function call in the files with the new name
-            left = left.replace(&name, &left_func);
-            right = right.replace(&name, &right_func);
+            left = replace_identifier(&left, &name, &left_func);
+            right = replace_identifier(&right, &name, &right_func);
             continue;
         }
         // it IS native to baba
@@ -127,26 +172,32 @@ pub fn merge_files(
             // only one function uses the injection method
             (true, false) | (false, true) => {
                 // this binding ensures that `injected` is *always* the injected method
-                let (injected, not_injected, rename) =
+                let (injected, not_injected) =
                     if left_file.function_uses_injection(&left_func.definition()) {
-                        (left_func, right_func, left_file.injection_data(func))
+                        (left_func, right_func)
                     } else {
-                        (right_func, left_func, right_file.injection_data(func))
+                        (right_func, left_func)
                     };
+                // the rename comes from both files combined, so either side can provide it
+                let rename = renamed_functions.get(&func.name()).cloned();
                 // The non-injected version needs to go first
                 merged.push_str(not_injected.code());
                 // then we add the variable definition that allows the
                 // injected version to work
                 let Some(rename) = rename else {
-                    return Err(ModdingError::RenameError)?;
+                    return Err(ModdingError::RenameError {
+                        function: func.name(),
+                        names: Vec::new(),
+                    })?;
                 };
                 let name = func.name();
                 let line = format!("local {} = {}", rename, name);
                 merged.push('\n');
                 merged.push_str(&line);
-                // then we add the injection version of the function
+                // then we add the injection version of the function,
+                // making sure it actually calls the original
                 merged.push('\n');
-                merged.push_str(injected.code());
+                merged.push_str(&injection_wrapper(&injected, &rename));
                 continue;
             }
             // neither function uses the injection method
@@ -161,16 +212,95 @@ pub fn merge_files(
     // Now that all the issues have been ironed out,
     // we can concatenate the two files together
     // with no issues! hopefully
-    let mut result = concat_strings(left, concat_strings(merged, right));
+    let result = concat_strings(left, concat_strings(merged, right));
 
     // Some final touch ups:
-    // remove any instances of double line breaks
-    while result.contains("\n\n") {
-        result = result.replace("\n\n", "\n");
+    // remove any excess blank lines
+    let result = collapse_blank_lines(&result);
+    // and make sure nothing went horribly wrong
+    if let Err(error) = validate_structure(&result) {
+        let region = result.lines().nth(error.line - 1).unwrap_or_default();
+        return Err(
+            ModdingError::MergeProducedInvalidLua(error.to_string(), region.to_owned()).into(),
+        );
     }
     Ok(result.into())
 }
 
+/// Returns `name` if it isn't taken, otherwise `name` followed by the smallest number (from 2) that isn't taken.
+///
+/// The returned name is added to `taken`.
+fn unused_name(name: String, taken: &mut HashSet<String>) -> String {
+    let name = match taken.contains(&name) {
+        false => name,
+        true => (2..)
+            .map(|number| format!("{}{}", name, number))
+            .find(|candidate| !taken.contains(candidate))
+            .expect("there are only finitely many taken names"),
+    };
+    taken.insert(name.clone());
+    name
+}
+
+/// Merges two [`LuaFile`]s (see [`merge_files`]) without writing anything,
+/// returning a unified diff between the two files as they were and the merged result.
+///
+/// This lets the merge be previewed and approved before it is written to disk.
+///
+/// # Errors
+/// Errors under the same circumstances as [`merge_files`], or if the diff could not be created.
+pub fn merge_files_diff(
+    left_file: LuaFile,
+    right_file: LuaFile,
+    baba_funcs: &[LuaFunction],
+) -> Result<String, BabaError> {
+    let original = concat_strings(left_file.code(), right_file.code());
+    let merged = merge_files(left_file, right_file, baba_funcs)?.code();
+    let dmp = DiffMatchPatch::new();
+    let diffs = dmp.diff_main::<DiffMode>(&original, &merged)?;
+    let patches = dmp.patch_make(PatchInput::new_text_diffs(&original, &diffs))?;
+    Ok(dmp.patch_to_text(&patches))
+}
+
+/// Collapses every run of three or more line breaks down to two.
+///
+/// This leaves at most one blank line between any two lines of code,
+/// so deliberate spacing (e.g. between functions) is kept.
+pub fn collapse_blank_lines(code: &str) -> String {
+    let mut result = String::with_capacity(code.len());
+    let mut line_breaks = 0;
+    for char in code.chars() {
+        if char == '\n' {
+            line_breaks += 1;
+            if line_breaks > 2 {
+                continue;
+            }
+        } else {
+            line_breaks = 0;
+        }
+        result.push(char);
+    }
+    result
+}
+
+/// Creates the code for an injected function that forwards its arguments to the original.
+///
+/// `rename` is the name the original function was stored under (e.g. `oldinit` in `local oldinit = init`).
+/// If the injected function never calls `rename`, a call forwarding the function's own
+/// arguments (see [`crate::mods::luafuncdef::LuaFuncDef::args`]) is added at the start of its body,
+/// so it runs even when the function returns early.
+pub fn injection_wrapper(injected: &LuaFunction, rename: &str) -> String {
+    let code = injected.code();
+    if called_names(code).contains(rename) {
+        return code.to_owned();
+    }
+    let call = format!("{}({})", rename, injected.definition().args());
+    match code.find(')') {
+        Some(index) => format!("{}\n\t{}{}", &code[..=index], call, &code[index + 1..]),
+        None => format!("{}\n\t{}\nend", code, call),
+    }
+}
+
 /// Merges two Lua Functions, assuming both are override functions.
 /// # Prereqs
 /// - Both functions should be checked beforehand to ensure they do not use the injection method.
@@ -265,22 +395,56 @@ fn merge_functions_via_dmp(
 }
 
 fn config_from_two_mods(left: &BabaMod, right: &BabaMod) -> Config {
-    let id = concat_strings(left.mod_id(), right.mod_id()).replace('\n', "");
-    let left_name = left.name();
-    let right_name = right.name();
-    let left_auth = format!("{:?}", left.authors())
-        .replace('[', "")
-        .replace(']', "");
-    let right_auth = format!("{:?}", right.authors())
-        .replace('[', "")
-        .replace(']', "");
-    let left_desc = left.description();
-    let right_desc = right.description();
+    config_from_mods(&[left, right])
+}
+
+/// Lists names in prose, e.g. `a, b and c`.
+fn list_names(names: &[String]) -> String {
+    match names {
+        [] => String::new(),
+        [name] => name.clone(),
+        [rest @ .., last] => format!("{} and {}", rest.join(", "), last),
+    }
+}
+
+/// Builds the ID of a mod merged from mods with the given IDs, e.g. `modA_x_modB`.
+///
+/// Only ASCII letters, digits and underscores are kept, and the result is cut off
+/// at [`MAX_MERGED_ID_LENGTH`]. The same IDs always give the same result.
+pub fn merged_mod_id(ids: &[String]) -> String {
+    let mut id = ids
+        .iter()
+        .map(|id| {
+            id.chars()
+                .filter(|char| char.is_ascii_alphanumeric() || *char == '_')
+                .collect::<String>()
+        })
+        .filter(|id| !id.is_empty())
+        .collect::<Vec<_>>()
+        .join(MERGED_ID_SEPARATOR);
+    if id.is_empty() {
+        id = "merged".to_owned();
+    }
+    // everything left is ASCII, so this can't cut a character in half
+    id.truncate(MAX_MERGED_ID_LENGTH);
+    id
+}
+
+fn config_from_mods(mods: &[&BabaMod]) -> Config {
+    let ids: Vec<String> = mods.iter().map(|baba_mod| baba_mod.mod_id()).collect();
+    let id = merged_mod_id(&ids);
+    let names: Vec<String> = mods.iter().map(|baba_mod| baba_mod.name()).collect();
+    let authors = clean_author_names(mods.iter().flat_map(|baba_mod| baba_mod.authors()));
+    let descriptions: String = mods
+        .iter()
+        .map(|baba_mod| format!("\n{}:{}", baba_mod.name(), baba_mod.description()))
+        .collect();
+    let names = list_names(&names);
 
     let config = serde_json::json! ({
-        "modid": format!("{id}"),
-        "authors": [format!("Authors of {left_name}: {left_auth}"), format!("Authors of {right_name}: {right_auth}")],
-        "description": format!("A merger between {left_name} and {right_name}, automatically generated by BMM.\n{left_name}:{left_desc}\n{right_name}:{right_desc}"),
+        "modid": id,
+        "authors": authors,
+        "description": format!("A merger between {names}, automatically generated by BMM.{descriptions}"),
         "icon_url": "",
         "banner_url": "",
         "global": false,
@@ -308,7 +472,7 @@ pub fn merge_mods(
     let right_files = right.lua_files(init);
 
     let folding_function = |accum: Result<_, _>, next| match accum
-        .map(|current_file| merge_files(current_file, next, &funcs))
+        .map(|current_file| merge_files_with(current_file, next, &funcs, &options.suffixes))
     {
         Ok(c) => match c {
             Ok(file) => Ok(file),
@@ -323,3 +487,82 @@ pub fn merge_mods(
     let resultant_file = fold?;
     BabaMod::init_with_options(resultant_file, options.location.clone(), config, options)
 }
+
+/// Merges any number of mods into one, creating a new mod at [`MergeOptions::location`].
+///
+/// The mods are merged one at a time, in order: the first two are merged, then the third is merged
+/// into the result, and so on. As with [`merge_files`], earlier mods have priority over later ones.
+/// The order can be controlled with [`MergeOptions::priority`].
+///
+/// # Errors
+/// This function may error if:
+/// - Fewer than two mods were given ([`ModdingError::NotEnoughMods`])
+/// - Merging any of the mods failed, naming the mods involved ([`ModdingError::MergeStepFailed`])
+/// - The merged mod could not be written (see [`BabaMod::init_with_options`])
+pub fn merge_many(
+    mods: &[&BabaMod],
+    baba_funcs: &[LuaFunction],
+    options: MergeOptions,
+) -> Result<BabaMod, BabaError> {
+    merge_many_with_progress(mods, baba_funcs, options, |_| {})
+}
+
+/// Merges any number of mods into one, as with [`merge_many`], reporting on the way.
+///
+/// Each lua file of each mod is one step of the merge, and `progress` is called
+/// once every step is done (see [`MergeProgress`]).
+///
+/// # Errors
+/// See [`merge_many`].
+pub fn merge_many_with_progress(
+    mods: &[&BabaMod],
+    baba_funcs: &[LuaFunction],
+    options: MergeOptions,
+    mut progress: impl FnMut(MergeProgress),
+) -> Result<BabaMod, BabaError> {
+    if mods.len() < 2 {
+        return Err(ModdingError::NotEnoughMods(mods.len()).into());
+    }
+    let mods = by_priority(mods, &options.priority);
+    let files: Vec<Vec<LuaFile>> = mods
+        .iter()
+        .map(|baba_mod| baba_mod.lua_files(options.include_init))
+        .collect();
+    let total = files.iter().map(Vec::len).sum();
+    let mut current = 0;
+    let mut merged = LuaFile::from("");
+    for (index, (baba_mod, files)) in mods.iter().zip(files).enumerate() {
+        for file in files {
+            merged =
+                merge_files_with(merged, file, baba_funcs, &options.suffixes).map_err(|error| {
+                    ModdingError::MergeStepFailed(
+                        mods[..index].iter().map(|done| done.mod_id()).collect(),
+                        baba_mod.mod_id(),
+                        error.to_string(),
+                    )
+                })?;
+            current += 1;
+            progress(MergeProgress {
+                current,
+                total,
+                label: baba_mod.mod_id(),
+            });
+        }
+    }
+    let config = config_from_mods(&mods);
+    BabaMod::init_with_options(merged, options.location.clone(), config, options)
+}
+
+/// Sorts mods by where their IDs are found in `priority`.
+/// Mods that aren't in the list are put at the end, keeping the order they were given in.
+fn by_priority<'a>(mods: &[&'a BabaMod], priority: &[String]) -> Vec<&'a BabaMod> {
+    let mut sorted = mods.to_vec();
+    sorted.sort_by_key(|baba_mod| {
+        let id = baba_mod.mod_id();
+        priority
+            .iter()
+            .position(|listed| *listed == id)
+            .unwrap_or(priority.len())
+    });
+    sorted
+}
@@ -1,14 +1,37 @@
-use std::{collections::HashSet, fs, path::PathBuf, str::FromStr};
+use std::{
+    cell::RefCell,
+    collections::HashSet,
+    env, fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+#[cfg(feature = "gui")]
+use egui::ColorImage;
+use zip::{write::SimpleFileOptions, ZipArchive, ZipWriter};
 
+#[cfg(feature = "gui")]
+use crate::application::load_image_from_path;
 use crate::{
-    error::babaerror::BabaError,
-    files::{babafiles::BabaFiles, luafile::LuaFile, writeinto::WriteInto, CONFIG_FILE_NAME},
+    error::{babaerror::BabaError, moddingerror::ModdingError},
+    files::{
+        babafiles::BabaFiles, find_config_file, luafile::LuaFile, native_file_stem,
+        writeinto::WriteInto, zip_recursively,
+    },
+    levelpack::{levelpackfile::LevelpackFile, WORLD_DATA_FILE_NAME},
     merge::{merge_mods, mergeoptions::MergeOptions},
 };
 
 use super::{
-    config::Config, functions_from_string, is_lua_file, luafuncdef::LuaFuncDef,
+    clean_author_names,
+    compatibility::Compatibility,
+    config::{Config, ConfigIssue},
+    functions_from_reader, is_glob_pattern, is_lua_file, is_probably_text,
+    luafuncdef::LuaFuncDef,
     luafunction::LuaFunction,
+    manifest::{add_to_manifest, Manifest, ManifestDiff},
+    modkind::ModKind,
+    sprite_matches, ARCHIVE_EXTRACTION_DIR, SPRITES_SEARCH_DEPTH,
 };
 
 /// Represents a Mod in Baba is You
@@ -20,6 +43,32 @@ pub struct BabaMod {
     config: Option<Config>,
     /// The name of the mod
     name: String,
+    /// Whether the mod is a folder or a single file, decided when the mod is created
+    kind: ModKind,
+    /// Data read from disk about the mod, see [`BabaMod::invalidate_cache`]
+    cache: ModCache,
+}
+
+/// Results of scanning a mod on disk, kept so that repeated calls
+/// (e.g. once per frame in the UI) don't rescan the file system.
+#[derive(Debug, Default)]
+struct ModCache {
+    /// See [`BabaMod::all_relevant_files`]
+    relevant_files: RefCell<Option<Vec<PathBuf>>>,
+    /// See [`BabaMod::defined_function_definitions`]
+    function_definitions: RefCell<Option<HashSet<LuaFuncDef>>>,
+    /// See [`BabaMod::defined_functions`]
+    functions: RefCell<Option<HashSet<LuaFunction>>>,
+}
+
+/// Returns the value held in the cell, or computes (and stores) it if there isn't one.
+fn cached<T: Clone>(cell: &RefCell<Option<T>>, compute: impl FnOnce() -> T) -> T {
+    if let Some(value) = cell.borrow().as_ref() {
+        return value.clone();
+    }
+    let value = compute();
+    *cell.borrow_mut() = Some(value.clone());
+    value
 }
 
 impl BabaMod {
@@ -35,54 +84,302 @@ impl BabaMod {
             .unwrap_or("[Invalid Name!]".into())
             .into_string()
             .unwrap_or("[No name Given!]".to_owned());
-        let config = Config::new(path.join(CONFIG_FILE_NAME)).ok();
-        Self { path, config, name }
+        let config = Config::load(&path).ok();
+        Self {
+            kind: ModKind::of(&path),
+            path,
+            config,
+            name,
+            cache: ModCache::default(),
+        }
     }
 
-    /// Partially initializes a mod.
+    /// Creates a new BabaMod from a `.zip` archive (as mods are usually distributed).
+    ///
+    /// The archive is extracted into a folder inside the system's temp directory
+    /// (see [`ARCHIVE_EXTRACTION_DIR`]), and the mod is then found inside of it:
+    /// - Archives that hold everything inside of a single top folder are flattened
+    /// - The mod is the folder that holds the config file (either the top of the archive, or a folder inside of it)
+    /// - If no config file exists, a single lua file at the top of the archive is loaded as a singleton
+    ///
+    /// # Errors
+    /// This function may error if:
+    /// - The archive could not be read or extracted ([`zip::result::ZipError`] or [`std::io::Error`])
+    /// - No mod could be found inside of the archive ([`ModdingError::NoModInArchive`])
+    pub fn from_archive(path: &Path) -> Result<Self, BabaError> {
+        let stem = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("archive");
+        let destination = env::temp_dir().join(ARCHIVE_EXTRACTION_DIR).join(stem);
+        // clear out anything left over from a previous extraction
+        if destination.exists() {
+            fs::remove_dir_all(&destination)?;
+        }
+        let mut archive = ZipArchive::new(fs::File::open(path)?)?;
+        archive.extract(&destination)?;
+
+        // flatten any single top folders
+        let mut root = destination;
+        loop {
+            let entries = root.read_dir()?.flatten().collect::<Vec<_>>();
+            match entries.as_slice() {
+                [entry] if entry.path().is_dir() => root = entry.path(),
+                _ => break,
+            }
+        }
+        // the root itself is the mod
+        if find_config_file(&root).is_some() {
+            return Ok(Self::new(root));
+        }
+        // a folder inside of the root is the mod
+        let entries = root
+            .read_dir()?
+            .flatten()
+            .map(|entry| entry.path())
+            .collect::<Vec<_>>();
+        if let Some(folder) = entries
+            .iter()
+            .find(|entry| find_config_file(entry).is_some())
+        {
+            return Ok(Self::new(folder.clone()));
+        }
+        // the mod is just a lone lua file
+        match entries
+            .into_iter()
+            .filter(|entry| is_lua_file(entry))
+            .collect::<Vec<_>>()
+            .as_slice()
+        {
+            [file] => Ok(Self::new(file.clone())),
+            _ => Err(BabaError::Modding(ModdingError::NoModInArchive(
+                path.to_owned(),
+            ))),
+        }
+    }
+
+    /// Packs the mod into a `.zip` archive at `out`, ready to be shared.
+    ///
+    /// The archive is laid out the same way the mod is installed into a levelpack, so that
+    /// [`BabaMod::from_archive`] can read it back:
+    /// - The mod itself (its folder, config included, or its lone lua file) is at the top of the archive
+    /// - Its init file, and any other files its config lists outside of its folder, sit next to it
+    /// - Its sprites are inside of a `Sprites` folder
+    ///
+    /// # Errors
+    /// This function may error if the mod's files could not be read, or the archive could not be written
+    /// ([`std::io::Error`] or [`zip::result::ZipError`])
+    pub fn export_as_zip(&self, out: &Path) -> Result<(), BabaError> {
+        let entries = self.archive_entries()?;
+        if let Some(parent) = out.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut writer = ZipWriter::new(fs::File::create(out)?);
+        let options = SimpleFileOptions::default();
+        for (name, path) in entries {
+            zip_recursively(&mut writer, &path, &name, options)?;
+        }
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// Lists everything that goes at the top of the mod's archive (see [`BabaMod::export_as_zip`]),
+    /// as the name it is given inside of the archive, and the path it is read from.
+    fn archive_entries(&self) -> Result<Vec<(String, PathBuf)>, BabaError> {
+        let sprites: String = LevelpackFile::Sprites.into();
+        let mod_sprites = self.sprites_folder().ok();
+        let mut paths = self.all_relevant_files()?;
+        paths.extend(self.init_path());
+        let mut result: Vec<(String, PathBuf)> = Vec::new();
+        for path in paths {
+            // anything inside of the mod's folder is added along with it
+            if path != self.path && path.starts_with(&self.path) {
+                continue;
+            }
+            if !path.exists() {
+                continue;
+            }
+            let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let name = match &mod_sprites {
+                Some(folder) if path.starts_with(folder) => format!("{}/{}", sprites, file_name),
+                _ => file_name.to_owned(),
+            };
+            // the init file may be listed in the config's files as well
+            if result.iter().all(|(added, _)| *added != name) {
+                result.push((name, path));
+            }
+        }
+        Ok(result)
+    }
+
+    /// Creates a [`Manifest`] of the mod, digesting every file that would be exported
+    /// with [`BabaMod::export_as_zip`] (keyed by its path inside of the archive).
+    ///
+    /// The manifest can be saved as json, and checked later with [`BabaMod::verify_against`].
+    ///
+    /// # Errors
+    /// This function may error if any of the mod's files could not be read ([`std::io::Error`])
+    pub fn manifest(&self) -> Result<Manifest, BabaError> {
+        let mut manifest = Manifest::new();
+        for (name, path) in self.archive_entries()? {
+            add_to_manifest(&mut manifest, &path, &name)?;
+        }
+        Ok(manifest)
+    }
+
+    /// Checks the mod's files against a [`Manifest`] (e.g. one published alongside the mod),
+    /// reporting every file that was added, removed or changed since.
+    ///
+    /// # Errors
+    /// This function may error if any of the mod's files could not be read ([`std::io::Error`])
+    pub fn verify_against(&self, manifest: &Manifest) -> Result<ManifestDiff, BabaError> {
+        Ok(ManifestDiff::between(manifest, &self.manifest()?))
+    }
+
+    /// Partially initializes a mod, returning the path to its newly created folder.
+    /// Any missing parent folders are created along the way.
     /// Do not use this; use [BabaMod::init] or [BabaMod::init_with_options]
-    fn partial_init(path: &mut PathBuf, config: &Config) -> Result<String, BabaError> {
+    fn partial_init(path: &Path, config: &Config) -> Result<PathBuf, BabaError> {
         let mod_id = config.modid();
+        let mod_path = path.join(&mod_id);
+        // this also creates `path` itself, for the init file
+        fs::create_dir_all(&mod_path)?;
         let init_file_path = config.suitable_init();
         let init_file = include_str!("../../src/data/init.lua").replace("__name__", &mod_id);
-        init_file.write_into_using(&*path, &init_file_path)?;
-        path.push(&mod_id);
-        fs::create_dir(&*path)?;
-        config.write_into(&*path)?;
-        Ok(mod_id)
+        init_file.write_into_using(path, &init_file_path)?;
+        config.write_into(&mod_path)?;
+        Ok(mod_path)
     }
 
     /// Initializes a new mod, given a single lua file, a path to put it in, and a config
-    pub fn init(file: LuaFile, mut path: PathBuf, config: Config) -> Result<Self, BabaError> {
-        let mod_id = Self::partial_init(&mut path, &config)?;
+    pub fn init(file: LuaFile, path: PathBuf, config: Config) -> Result<Self, BabaError> {
+        let path = Self::partial_init(&path, &config)?;
         // lua file
         file.write_into(&path)?;
         Ok(Self {
             path,
+            name: config.modid(),
+            kind: ModKind::Directory,
             config: Some(config),
-            name: mod_id,
+            cache: ModCache::default(),
         })
     }
 
     /// Initializes a new mod, given a single lua file, a path to put it in, a config, and [MergeOptions].
+    ///
+    /// # Errors
+    /// If a mod with the same ID already exists at the path, this errors with
+    /// [`ModdingError::MergeTargetExists`] unless [`MergeOptions::overwrite`] is set,
+    /// in which case the existing mod's folder is removed first.
     pub fn init_with_options(
         file: LuaFile,
-        mut path: PathBuf,
+        path: PathBuf,
         config: Config,
         options: MergeOptions,
     ) -> Result<Self, BabaError> {
-        let mod_id = Self::partial_init(&mut path, &config)?;
+        let target = path.join(config.modid());
+        if target.exists() {
+            if !options.overwrite {
+                return Err(ModdingError::MergeTargetExists(target).into());
+            }
+            fs::remove_dir_all(&target)?;
+        }
+        let path = Self::partial_init(&path, &config)?;
         file.write_into_using(&path, &options.file_name)?;
         Ok(Self {
             path,
+            name: config.modid(),
+            kind: ModKind::Directory,
             config: Some(config),
-            name: mod_id,
+            cache: ModCache::default(),
         })
     }
 
+    /// Gets the path to the mod (either its folder, or the file itself for singletons)
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Gets the path to the mod's init file, if its config declares one.
+    ///
+    /// The init file lives next to the mod, so it is resolved relative to the mod's parent folder.
+    pub fn init_path(&self) -> Option<PathBuf> {
+        let init = self.config.as_ref()?.init()?;
+        Some(self.path.parent()?.join(init))
+    }
+
+    /// Gets whether the mod is a folder or a single file
+    pub fn kind(&self) -> ModKind {
+        self.kind
+    }
+
     /// Reports whether the mod is a singleton (i.e. a standalone lua file)
     pub fn is_singleton(&self) -> bool {
-        self.path.extension().is_some()
+        self.kind == ModKind::File
+    }
+
+    /// Returns the name of the native baba file this mod replaces wholesale, if any.
+    ///
+    /// This is only the case for singletons whose file name matches one of baba's own
+    /// lua files (e.g. a user-dropped `movement.lua`). Such mods can't be merged the same
+    /// way as mods that override individual functions.
+    pub fn overrides_native_file(&self) -> Option<String> {
+        if !self.is_singleton() {
+            return None;
+        }
+        native_file_stem(&self.path)
+    }
+
+    /// Loads the mod's icon, from its config's `icon_url` (see [`Config::fetch_icon`]).
+    ///
+    /// Returns `Ok(None)` if the mod has no config, or its config has no icon.
+    ///
+    /// # Errors
+    /// This function may error if the icon could not be fetched, or could not be decoded as an image
+    #[cfg(feature = "gui")]
+    pub fn icon(&self, cache_dir: &Path) -> Result<Option<ColorImage>, BabaError> {
+        let Some(config) = &self.config else {
+            return Ok(None);
+        };
+        load_optional_image(config.fetch_icon(cache_dir)?)
+    }
+
+    /// Loads the mod's banner, from its config's `banner_url` (see [`Config::fetch_banner`]).
+    ///
+    /// Returns `Ok(None)` if the mod has no config, or its config has no banner.
+    ///
+    /// # Errors
+    /// This function may error if the banner could not be fetched, or could not be decoded as an image
+    #[cfg(feature = "gui")]
+    pub fn banner(&self, cache_dir: &Path) -> Result<Option<ColorImage>, BabaError> {
+        let Some(config) = &self.config else {
+            return Ok(None);
+        };
+        load_optional_image(config.fetch_banner(cache_dir)?)
+    }
+
+    /// Returns whether the mod is installed game-wide (see [`Config::is_global`]).
+    /// Mods without a config are never global.
+    pub fn is_global(&self) -> bool {
+        self.config.as_ref().is_some_and(Config::is_global)
+    }
+
+    /// Returns the folder of the levelpack this mod is installed into,
+    /// i.e. if the mod is inside of a levelpack's `Lua` folder.
+    ///
+    /// Mods that aren't inside of a levelpack (such as global mods, which live in baba's own `Lua` folder)
+    /// return [`None`].
+    pub fn levelpack_dir(&self) -> Option<PathBuf> {
+        let lua_dir = self.path.parent()?;
+        let is_lua_dir = lua_dir
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.parse::<LevelpackFile>().ok())
+            == Some(LevelpackFile::Lua);
+        let pack_dir = lua_dir.parent()?;
+        (is_lua_dir && pack_dir.join(WORLD_DATA_FILE_NAME).is_file()).then(|| pack_dir.to_owned())
     }
 
     /// Returns whether this mod has a config file associated with it.
@@ -90,13 +387,51 @@ impl BabaMod {
         self.config.is_some()
     }
 
-    /// Gets the path for the sprites folder
-    pub fn sprites_folder(&self) -> PathBuf {
-        self.path.join(r"..\..\Sprites")
+    /// Gets the path for the sprites folder.
+    ///
+    /// This walks up from the mod (whether it's a folder or a singleton) until
+    /// a folder containing a `Sprites` folder is found, i.e. the levelpack or the
+    /// root of the global mods.
+    ///
+    /// # Errors
+    /// Returns [`ModdingError::SpritesFolderNotFound`] if no `Sprites` folder is found
+    /// within [`SPRITES_SEARCH_DEPTH`] parents of the mod.
+    pub fn sprites_folder(&self) -> Result<PathBuf, BabaError> {
+        let sprites: String = LevelpackFile::Sprites.into();
+        self.path
+            .ancestors()
+            .skip(1)
+            .take(SPRITES_SEARCH_DEPTH)
+            .map(|ancestor| ancestor.join(&sprites))
+            .find(|candidate| candidate.is_dir())
+            .ok_or(BabaError::Modding(ModdingError::SpritesFolderNotFound(
+                self.path.clone(),
+            )))
+    }
+
+    /// Forgets anything read from disk about this mod, so that the next
+    /// call to e.g. [`BabaMod::all_relevant_files`] rescans the file system.
+    pub fn invalidate_cache(&self) {
+        self.cache.relevant_files.take();
+        self.cache.function_definitions.take();
+        self.cache.functions.take();
     }
 
     /// Returns a vector of any relevant files to the mod.
+    ///
+    /// The result is cached, see [`BabaMod::invalidate_cache`].
     pub fn all_relevant_files(&self) -> Result<Vec<PathBuf>, BabaError> {
+        if let Some(files) = self.cache.relevant_files.borrow().as_ref() {
+            return Ok(files.clone());
+        }
+        let files = self.scan_relevant_files()?;
+        *self.cache.relevant_files.borrow_mut() = Some(files.clone());
+        Ok(files)
+    }
+
+    /// Reads the relevant files to the mod from disk.
+    /// Do not use this; use [`BabaMod::all_relevant_files`]
+    fn scan_relevant_files(&self) -> Result<Vec<PathBuf>, BabaError> {
         let mut result = Vec::new();
         result.push(self.path.clone());
         // If there's no config, we only worry about ourselves
@@ -107,30 +442,124 @@ impl BabaMod {
         config
             .files()
             .iter()
-            .for_each(|file| result.push(PathBuf::from(file)));
+            .for_each(|file| result.extend(self.expand_config_file(file)));
         // add sprites
-        let sprites = self.sprites_folder();
-        'outer: for sprite in sprites.read_dir()?.flatten().map(|entry| entry.path()) {
-            'inner: for held_name in self.defined_sprites() {
-                let Some(inspected_name) = sprite.file_name().and_then(|name| name.to_str()) else {
-                    continue 'inner;
-                };
-                if inspected_name.contains(&held_name) {
-                    result.push(sprite.clone());
-                    continue 'outer;
-                }
+        result.extend(self.sprite_files()?);
+        Ok(result)
+    }
+
+    /// Sums up the size (in bytes) of every file belonging to the mod, see [`BabaMod::all_relevant_files`].
+    ///
+    /// Folders are counted by their contents, and every file is only counted once,
+    /// even if it is listed more than once (e.g. in the config's `files`, and inside the mod's folder).
+    /// Listed files that don't exist are skipped.
+    ///
+    /// # Errors
+    /// Errors if any of the files or folders could not be read.
+    pub fn total_size(&self) -> Result<u64, BabaError> {
+        let mut counted = HashSet::new();
+        let mut total = 0;
+        for path in self.all_relevant_files()? {
+            total += disk_usage(&path, &mut counted)?;
+        }
+        Ok(total)
+    }
+
+    /// Turns an entry of the config's `files` into paths, relative to the mod's folder.
+    ///
+    /// Plain paths are kept as they are (whether or not they exist), while glob patterns
+    /// (see [`is_glob_pattern`]) are expanded into every path they match, which may be none.
+    fn expand_config_file(&self, entry: &str) -> Vec<PathBuf> {
+        let path = self.path.join(entry);
+        if !is_glob_pattern(entry) {
+            return vec![path];
+        }
+        let Some(pattern) = path.to_str() else {
+            return vec![];
+        };
+        glob::glob(pattern)
+            .map(|paths| paths.flatten().collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the paths of every sprite in the sprites folder that belongs to this mod,
+    /// as declared in its config.
+    ///
+    /// # Errors
+    /// Will only throw an error if the directory from [`BabaMod::sprites_folder`] is unable to be found or read
+    pub fn sprite_files(&self) -> Result<Vec<PathBuf>, BabaError> {
+        let declared = self.defined_sprites();
+        if declared.is_empty() {
+            return Ok(vec![]);
+        }
+        let mut result = Vec::new();
+        for sprite in self
+            .sprites_folder()?
+            .read_dir()?
+            .flatten()
+            .map(|entry| entry.path())
+        {
+            let Some(inspected_name) = sprite.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            if declared
+                .iter()
+                .any(|held_name| sprite_matches(held_name, inspected_name))
+            {
+                result.push(sprite);
             }
         }
         Ok(result)
     }
 
+    /// Checks the mod's config against the files actually on disk, like a linter for mod authors.
+    ///
+    /// Every listed file should exist (relative to the mod's folder), every listed sprite
+    /// should match a file in the sprites folder, and the init file (if any) should exist.
+    /// A mod without a config has nothing to check.
+    pub fn validate_config(&self) -> Vec<ConfigIssue> {
+        let Some(config) = &self.config else {
+            return Vec::new();
+        };
+        let mut result = Vec::new();
+        for entry in config.files() {
+            let paths = self.expand_config_file(&entry);
+            if is_glob_pattern(&entry) {
+                if paths.is_empty() {
+                    result.push(ConfigIssue::PatternMatchedNothing(entry));
+                }
+                continue;
+            }
+            result.extend(
+                paths
+                    .into_iter()
+                    .filter(|path| !path.exists())
+                    .map(ConfigIssue::MissingFile),
+            );
+        }
+        if !config.sprites().is_empty() {
+            match self.unused_sprite_declarations() {
+                Ok(missing) => result.extend(missing.into_iter().map(ConfigIssue::MissingSprite)),
+                Err(_) => result.push(ConfigIssue::NoSpritesFolder),
+            }
+        }
+        if let Some(init) = self.init_path().filter(|init| !init.exists()) {
+            result.push(ConfigIssue::MissingInit(init));
+        }
+        result
+    }
+
     /// Returns a vector of all lua file paths that the mod uses.
+    ///
+    /// Files are picked out by their extension (see [`is_lua_file`]), and anything that
+    /// doesn't look like text (see [`is_probably_text`]) is left out, so that e.g. sprites
+    /// are never handed to the lua parser.
     pub fn lua_file_paths(&self, include_init: bool) -> Vec<PathBuf> {
         let mut result: Vec<PathBuf> = self
             .all_relevant_files()
             .unwrap_or_default()
             .into_iter()
-            .filter(|path: &PathBuf| is_lua_file(path))
+            .filter(|path: &PathBuf| is_lua_file(path) && is_probably_text(path))
             .collect();
         if include_init {
             if let Some(config) = &self.config {
@@ -153,31 +582,39 @@ impl BabaMod {
     /// Returns a set of functions that the mod defines.
     /// This is a [`HashSet`] of [`LuaFuncDef`]s, best for comparing
     /// this mod against another.
+    ///
+    /// The result is cached, see [`BabaMod::invalidate_cache`].
     pub fn defined_function_definitions(&self) -> HashSet<LuaFuncDef> {
+        cached(&self.cache.function_definitions, || {
+            self.scan_function_definitions()
+        })
+    }
+
+    /// Reads the function definitions of the mod from disk.
+    /// Do not use this; use [`BabaMod::defined_function_definitions`]
+    fn scan_function_definitions(&self) -> HashSet<LuaFuncDef> {
         let mut result = HashSet::new();
-        let iter = self
-            .all_relevant_files()
-            .unwrap_or_default()
-            .into_iter()
-            .filter(|path: &PathBuf| is_lua_file(path));
-        for file in iter {
-            let Ok(contents) = fs::read_to_string(file) else {
+        for file in self.lua_file_paths(false) {
+            let Ok(file) = fs::File::open(file) else {
                 continue;
             };
-            let set = functions_from_string(&contents);
-            result = result.union(&set).map(Clone::clone).collect();
+            result.extend(functions_from_reader(io::BufReader::new(file)));
         }
         result
     }
 
+    /// Returns a set of functions (code included) that the mod defines.
+    ///
+    /// The result is cached, see [`BabaMod::invalidate_cache`].
     pub fn defined_functions(&self) -> HashSet<LuaFunction> {
+        cached(&self.cache.functions, || self.scan_functions())
+    }
+
+    /// Reads the functions of the mod from disk.
+    /// Do not use this; use [`BabaMod::defined_functions`]
+    fn scan_functions(&self) -> HashSet<LuaFunction> {
         let mut result = HashSet::new();
-        let iter = self
-            .all_relevant_files()
-            .unwrap_or_default()
-            .into_iter()
-            .filter(|path: &PathBuf| is_lua_file(path));
-        for file in iter {
+        for file in self.lua_file_paths(false) {
             let Ok(contents) = fs::read_to_string(file) else {
                 continue;
             };
@@ -197,28 +634,102 @@ impl BabaMod {
         }
     }
 
+    /// Lists the sprites declared in the mod's config that have no matching file in the sprites folder,
+    /// in the order they are declared. These are usually left over from sprites that were deleted.
+    ///
+    /// # Errors
+    /// Will only throw an error if the directory from [`BabaMod::sprites_folder`] is unable to be found or read
+    pub fn unused_sprite_declarations(&self) -> Result<Vec<String>, BabaError> {
+        let Some(config) = &self.config else {
+            return Ok(vec![]);
+        };
+        let declared = config.sprites();
+        if declared.is_empty() {
+            return Ok(vec![]);
+        }
+        let sprites = self.sprites_by_name()?;
+        Ok(declared
+            .into_iter()
+            .filter(|held_name| {
+                !sprites
+                    .iter()
+                    .any(|sprite| sprite_matches(held_name, sprite))
+            })
+            .collect())
+    }
+
+    /// Lists the files in the sprites folder (by name, sorted) that aren't declared in the mod's config,
+    /// the opposite of [`BabaMod::unused_sprite_declarations`].
+    ///
+    /// Note that in a levelpack, the sprites folder is shared, so this includes the sprites of other mods as well.
+    ///
+    /// # Errors
+    /// Will only throw an error if the directory from [`BabaMod::sprites_folder`] is unable to be found or read
+    pub fn undeclared_sprites(&self) -> Result<Vec<String>, BabaError> {
+        let declared = self.defined_sprites();
+        let mut result: Vec<String> = self
+            .sprites_by_name()?
+            .into_iter()
+            .filter(|sprite| {
+                !declared
+                    .iter()
+                    .any(|held_name| sprite_matches(held_name, sprite))
+            })
+            .collect();
+        result.sort();
+        Ok(result)
+    }
+
     /// Grabs all the sprites in the sprites folder by name
     ///
     /// # Errors
-    /// Will only throw an error if the directory from [`BabaMod::sprites_folder`] is unable to be read
+    /// Will only throw an error if the directory from [`BabaMod::sprites_folder`] is unable to be found or read
     pub fn sprites_by_name(&self) -> Result<HashSet<String>, BabaError> {
         Ok(self
-            .sprites_folder()
+            .sprites_folder()?
             .read_dir()?
             .flatten()
             .map(|x| x.file_name().into_string().unwrap_or_default())
             .collect())
     }
 
+    /// Compares this mod against another, listing every function and sprite they share.
+    ///
+    /// Sprites are compared by what each mod declares in its config, since mods
+    /// in the same levelpack share a single sprites folder.
+    ///
+    /// Mods installed into different levelpacks (see [`BabaMod::levelpack_dir`]) are always compatible,
+    /// unless either of them is global.
+    pub fn compatibility_with(&self, other: &Self) -> Compatibility {
+        // mods installed into different packs are never loaded together,
+        // unless one of them is global (in which case it's loaded alongside everything)
+        let scoped = !self.is_global() && !other.is_global();
+        if let (true, Some(left), Some(right)) =
+            (scoped, self.levelpack_dir(), other.levelpack_dir())
+        {
+            if left != right {
+                return Compatibility::Compatible;
+            }
+        }
+        let functions = self
+            .defined_function_definitions()
+            .intersection(&other.defined_function_definitions())
+            .cloned()
+            .collect();
+        let sprites = self
+            .defined_sprites()
+            .intersection(&other.defined_sprites())
+            .cloned()
+            .collect();
+        Compatibility::from_overlaps(functions, sprites)
+    }
+
     /// Returns whether this mod is compatible with another mod
     /// via way of function overrides & sprite checks.
+    ///
+    /// See [`BabaMod::compatibility_with`] for the reasons behind an incompatibility.
     pub fn is_compatible_with(&self, other: &Self) -> bool {
-        self.defined_function_definitions()
-            .is_disjoint(&other.defined_function_definitions())
-            && self
-                .sprites_by_name()
-                .unwrap_or_default()
-                .is_disjoint(&other.sprites_by_name().unwrap_or_default())
+        self.compatibility_with(other).is_compatible()
     }
 
     /// Gets the mod id, or if the config doesn't exist, gets the name instead
@@ -229,10 +740,18 @@ impl BabaMod {
         }
     }
 
-    /// Gets the list of authors, or if the config doesn't exist, returns an empty vector
+    /// Gets the list of authors (see [`clean_author_names`]), or if the config doesn't exist, returns an empty vector
     pub fn authors(&self) -> Vec<String> {
         match &self.config {
-            Some(config) => config.authors().clone(),
+            Some(config) => clean_author_names(config.authors()),
+            None => vec![],
+        }
+    }
+
+    /// Gets the tags of the mod, or if the config doesn't exist, returns an empty vector
+    pub fn tags(&self) -> Vec<String> {
+        match &self.config {
+            Some(config) => config.tags(),
             None => vec![],
         }
     }
@@ -254,3 +773,36 @@ impl BabaMod {
         merge_mods(self, other, files.native_baba_lua_functions(), options)
     }
 }
+
+/// Loads an image from disk, if there is one to load.
+#[cfg(feature = "gui")]
+fn load_optional_image(path: Option<PathBuf>) -> Result<Option<ColorImage>, BabaError> {
+    match path {
+        Some(path) => Ok(Some(load_image_from_path(&path)?)),
+        None => Ok(None),
+    }
+}
+
+/// The size (in bytes) of a file, or everything inside of a folder.
+///
+/// Files already in `counted` count as nothing, and every file counted is added to `counted`.
+fn disk_usage(path: &Path, counted: &mut HashSet<PathBuf>) -> Result<u64, io::Error> {
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(error) => return Err(error),
+    };
+    if metadata.is_dir() {
+        let mut total = 0;
+        for entry in fs::read_dir(path)? {
+            total += disk_usage(&entry?.path(), counted)?;
+        }
+        return Ok(total);
+    }
+    // the same file may be reached through different paths
+    if counted.insert(path.canonicalize()?) {
+        Ok(metadata.len())
+    } else {
+        Ok(0)
+    }
+}
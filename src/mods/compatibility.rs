@@ -0,0 +1,113 @@
+use super::luafuncdef::LuaFuncDef;
+
+/// How a single function shared by two mods can be resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OverlapKind {
+    /// Both mods override the same function native to baba,
+    /// which [`crate::merge::merge_files`] is able to merge together.
+    Mergeable,
+    /// Both mods define the same non-native function,
+    /// which is a genuine clash.
+    Conflict,
+}
+
+impl OverlapKind {
+    /// Classifies a function that is defined by two mods at once.
+    ///
+    /// This mirrors [`crate::merge::merge_files`]: native functions are merged,
+    /// and everything else is not.
+    pub fn of(definition: &LuaFuncDef) -> Self {
+        match definition.is_baba_native() {
+            true => Self::Mergeable,
+            false => Self::Conflict,
+        }
+    }
+}
+
+/// A function that two mods both define, alongside how it can be resolved.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FunctionOverlap {
+    /// The shared function
+    pub definition: LuaFuncDef,
+    /// Whether the overlap can be merged or not
+    pub kind: OverlapKind,
+}
+
+impl From<LuaFuncDef> for FunctionOverlap {
+    fn from(definition: LuaFuncDef) -> Self {
+        let kind = OverlapKind::of(&definition);
+        Self { definition, kind }
+    }
+}
+
+/// Describes how two mods relate to one another when installed side-by-side.
+///
+/// Created via [`crate::mods::babamod::BabaMod::compatibility_with`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Compatibility {
+    /// The two mods share no functions or sprites
+    Compatible,
+    /// The two mods collide on at least one function or sprite
+    Conflicting {
+        /// The functions that both mods define (sorted by name)
+        functions: Vec<FunctionOverlap>,
+        /// The sprites that both mods declare (sorted)
+        sprites: Vec<String>,
+    },
+}
+
+impl Compatibility {
+    /// Creates a [`Compatibility`] from a set of overlapping functions and sprites.
+    ///
+    /// If both are empty, the result is [`Compatibility::Compatible`].
+    pub fn from_overlaps(functions: Vec<LuaFuncDef>, mut sprites: Vec<String>) -> Self {
+        if functions.is_empty() && sprites.is_empty() {
+            return Self::Compatible;
+        }
+        let mut functions: Vec<FunctionOverlap> = functions.into_iter().map(Into::into).collect();
+        functions.sort_by_key(|overlap| overlap.definition.name());
+        sprites.sort();
+        Self::Conflicting { functions, sprites }
+    }
+
+    /// Returns whether the two mods share nothing at all.
+    pub fn is_compatible(&self) -> bool {
+        matches!(self, Self::Compatible)
+    }
+
+    /// Returns whether every overlap between the two mods can be resolved by merging them,
+    /// i.e. they only share native functions and no sprites.
+    pub fn is_mergeable(&self) -> bool {
+        self.hard_conflicts().next().is_none() && self.conflicting_sprites().is_empty()
+    }
+
+    /// Returns the shared functions that cannot be merged.
+    pub fn hard_conflicts(&self) -> impl Iterator<Item = &FunctionOverlap> {
+        self.conflicting_functions()
+            .iter()
+            .filter(|overlap| overlap.kind == OverlapKind::Conflict)
+    }
+
+    /// Returns the shared functions that can be merged.
+    pub fn mergeable_overlaps(&self) -> impl Iterator<Item = &FunctionOverlap> {
+        self.conflicting_functions()
+            .iter()
+            .filter(|overlap| overlap.kind == OverlapKind::Mergeable)
+    }
+
+    /// Returns the functions both mods define, if any.
+    pub fn conflicting_functions(&self) -> &[FunctionOverlap] {
+        match self {
+            Self::Compatible => &[],
+            Self::Conflicting { functions, .. } => functions,
+        }
+    }
+
+    /// Returns the sprites both mods declare, if any.
+    pub fn conflicting_sprites(&self) -> &[String] {
+        match self {
+            Self::Compatible => &[],
+            Self::Conflicting { sprites, .. } => sprites,
+        }
+    }
+}
@@ -1,8 +1,15 @@
-use std::{fs, path::PathBuf};
+use std::{
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+};
 
 use crate::{
     error::{babaerror::BabaError, moddingerror::ModdingError},
-    files::{writeinto::WriteInto, CONFIG_FILE_NAME},
+    files::{
+        fetch::fetch_cached, find_config_file, is_config_file_name, writeinto::WriteInto,
+        CONFIG_FILE_NAME,
+    },
 };
 
 /// Represents a configuration file for a mod, unique to the manager.
@@ -10,7 +17,7 @@ use crate::{
 ///
 /// # Notes
 ///
-#[derive(serde::Serialize, serde::Deserialize, Debug, Default, Clone)]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default, Clone, PartialEq)]
 pub struct Config {
     /// The mod ID, used for compatibilities
     modid: String,
@@ -34,21 +41,50 @@ pub struct Config {
     init: Option<String>,
     /// A list of sprites that belong to the mod
     sprites: Vec<String>,
+    /// Any fields not listed above, kept so that they survive being written back out
+    #[serde(flatten)]
+    extra: serde_json::Map<String, serde_json::Value>,
 }
 
 impl Config {
-    /// Tries to find a config file, given a path to it.
-    pub fn new(path: PathBuf) -> Result<Self, BabaError> {
-        if !path.ends_with(CONFIG_FILE_NAME) {
-            return Err(BabaError::Modding(ModdingError::NotAConfigFile(path)));
-        }
+    /// Loads a config, given either the path to the config file itself,
+    /// or the folder holding it (i.e. the mod's folder).
+    ///
+    /// The file name is matched case-insensitively, so if `[mod]\Config.json` doesn't exist,
+    /// `[mod]\config.json` (or any other spelling) is read instead.
+    ///
+    /// # Errors
+    /// This function may error if:
+    /// - The path is a file that isn't a config file ([`ModdingError::NotAConfigFile`])
+    /// - The config file could not be read ([`BabaError::IoAt`])
+    /// - The config file is not a valid config ([`serde_json::Error`])
+    pub fn load(path: &Path) -> Result<Self, BabaError> {
+        let path = match path.is_dir() {
+            true => find_config_file(path).unwrap_or_else(|| path.join(CONFIG_FILE_NAME)),
+            false if !is_config_file_name(path) => {
+                return Err(BabaError::Modding(ModdingError::NotAConfigFile(
+                    path.to_owned(),
+                )))
+            }
+            false if path.is_file() => path.to_owned(),
+            false => path
+                .parent()
+                .and_then(find_config_file)
+                .unwrap_or_else(|| path.to_owned()),
+        };
         // read out the file as a string
-        let file = fs::read_to_string(path)?;
+        let file = fs::read_to_string(&path).map_err(BabaError::io_at(&path))?;
         // parse it as a Config
         let config: Config = serde_json::from_str(&file)?;
         Ok(config)
     }
 
+    /// Tries to find a config file, given a path to it.
+    #[deprecated(note = "use `Config::load`, which also accepts the mod's folder")]
+    pub fn new(path: PathBuf) -> Result<Self, BabaError> {
+        Self::load(&path)
+    }
+
     pub fn files(&self) -> Vec<String> {
         self.files.clone()
     }
@@ -83,6 +119,56 @@ impl Config {
         self.sprites.clone()
     }
 
+    /// Gets the tags the mod is listed under
+    pub fn tags(&self) -> Vec<String> {
+        self.tags.clone()
+    }
+
+    /// Gets every link listed by the mod, as written (see [`Config::valid_links`] for only the usable ones)
+    pub fn links(&self) -> Vec<String> {
+        self.links.clone()
+    }
+
+    /// Gets the links listed by the mod that are actual `http`/`https` urls,
+    /// skipping placeholders such as `[Intentionally left without links]`.
+    pub fn valid_links(&self) -> Vec<String> {
+        self.links
+            .iter()
+            .filter(|link| is_web_url(link))
+            .cloned()
+            .collect()
+    }
+
+    /// Whether the mod is installed game-wide, rather than into a single levelpack
+    pub fn is_global(&self) -> bool {
+        self.global
+    }
+
+    /// Sets whether the mod is installed game-wide, rather than into a single levelpack
+    pub fn set_global(&mut self, global: bool) {
+        self.global = global;
+    }
+
+    /// Fetches the mod's icon (see [`fetch_cached`]), returning the path to it on disk.
+    ///
+    /// Returns `Ok(None)` if the mod has no icon.
+    pub fn fetch_icon(&self, cache_dir: &Path) -> Result<Option<PathBuf>, BabaError> {
+        fetch_optional(self.icon_url.as_deref(), cache_dir)
+    }
+
+    /// Fetches the mod's banner (see [`fetch_cached`]), returning the path to it on disk.
+    ///
+    /// Returns `Ok(None)` if the mod has no banner.
+    pub fn fetch_banner(&self, cache_dir: &Path) -> Result<Option<PathBuf>, BabaError> {
+        fetch_optional(self.banner_url.as_deref(), cache_dir)
+    }
+
+    /// Returns any fields in the config that the manager doesn't know about
+    /// (e.g. fields used by other tools).
+    pub fn extra(&self) -> &serde_json::Map<String, serde_json::Value> {
+        &self.extra
+    }
+
     /// creates a config directly from json data
     ///
     /// # Errors
@@ -93,10 +179,78 @@ impl Config {
     }
 }
 
+/// A problem found with a mod's config, when comparing it against the mod's files,
+/// see [`crate::mods::babamod::BabaMod::validate_config`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigIssue {
+    /// A file listed in the config does not exist (holds the path it was expected at)
+    MissingFile(PathBuf),
+    /// A glob pattern listed in the config's files matches no files
+    PatternMatchedNothing(String),
+    /// A sprite listed in the config has no matching file in the sprites folder
+    MissingSprite(String),
+    /// Sprites are listed in the config, but the mod has no sprites folder
+    NoSpritesFolder,
+    /// The config's init file does not exist (holds the path it was expected at)
+    MissingInit(PathBuf),
+}
+
+impl Display for ConfigIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            ConfigIssue::MissingFile(path_buf) => {
+                format!("The file {:?} is listed, but does not exist.", path_buf)
+            }
+            ConfigIssue::PatternMatchedNothing(pattern) => {
+                format!(
+                    "The pattern \"{}\" is listed, but does not match any files.",
+                    pattern
+                )
+            }
+            ConfigIssue::MissingSprite(sprite) => {
+                format!(
+                    "The sprite \"{}\" is listed, but no matching sprite was found.",
+                    sprite
+                )
+            }
+            ConfigIssue::NoSpritesFolder => {
+                "Sprites are listed, but no Sprites folder could be found.".to_string()
+            }
+            ConfigIssue::MissingInit(path_buf) => {
+                format!("The init file {:?} does not exist.", path_buf)
+            }
+        };
+        write!(f, "{}", message)
+    }
+}
+
+/// Returns whether a link is an `http` or `https` url with a host, e.g. `https://example.com/mod`.
+fn is_web_url(link: &str) -> bool {
+    let Some(rest) = link
+        .strip_prefix("https://")
+        .or_else(|| link.strip_prefix("http://"))
+    else {
+        return false;
+    };
+    let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    !host.is_empty() && !link.contains(char::is_whitespace)
+}
+
+/// Fetches a url that may not be given (or may be left empty).
+fn fetch_optional(url: Option<&str>, cache_dir: &Path) -> Result<Option<PathBuf>, BabaError> {
+    match url {
+        Some(url) if !url.is_empty() => fetch_cached(url, cache_dir).map(Some),
+        _ => Ok(None),
+    }
+}
+
 impl WriteInto for Config {
     const FILE_NAME: &str = CONFIG_FILE_NAME;
 
+    /// Pretty-prints the config, since users edit these by hand.
+    ///
+    /// Known fields keep the order they're declared in, and any extra fields follow in alphabetical order.
     fn as_file(&self) -> String {
-        serde_json::to_string(self).unwrap_or("{}".to_owned())
+        serde_json::to_string_pretty(self).unwrap_or("{}".to_owned())
     }
 }
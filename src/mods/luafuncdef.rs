@@ -1,16 +1,43 @@
-use std::str::FromStr;
+use std::{
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
 
 use serde::{Deserialize, Serialize};
 
 use crate::error::moddingerror::ModdingError;
 
-use super::baba_function_names;
+use super::is_baba_native_name;
 
 // A Lua function used in either a baba mod, or baba is you
-#[derive(Debug, Serialize, Deserialize, Hash, PartialEq, Eq, Clone)]
+//
+// Only the name is considered when comparing or hashing, since it's what identifies a function.
+// Whether it is baba-native is worked out from the name, and the arguments may be
+// spelled (or spaced) differently between declarations of the same function.
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct LuaFuncDef {
     name: String,
     is_baba_native: bool,
+    /// Whether the function was declared as `local function`
+    #[serde(default)]
+    is_local: bool,
+    /// Everything between the parentheses of the declaration, e.g. `a, b, c`
+    #[serde(default)]
+    args: String,
+}
+
+impl PartialEq for LuaFuncDef {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Eq for LuaFuncDef {}
+
+impl Hash for LuaFuncDef {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+    }
 }
 
 impl LuaFuncDef {
@@ -20,12 +47,39 @@ impl LuaFuncDef {
     pub fn name(&self) -> String {
         self.name.clone()
     }
+    /// Whether the function was declared as `local function name()`.
+    ///
+    /// Local functions are never baba-native, since they can't replace a global.
+    pub fn is_local(&self) -> bool {
+        self.is_local
+    }
+    /// The raw argument list of the function (everything between the parentheses)
+    pub fn args(&self) -> &str {
+        &self.args
+    }
+    /// Creates a copy of this definition under another name.
+    ///
+    /// Whether it is baba-native is worked out again for the new name.
+    pub fn renamed(&self, name: &str) -> Self {
+        LuaFuncDef {
+            name: name.to_owned(),
+            is_baba_native: !self.is_local && is_baba_native_name(name),
+            is_local: self.is_local,
+            args: self.args.clone(),
+        }
+    }
 }
 
 impl FromStr for LuaFuncDef {
     type Err = ModdingError;
 
     fn from_str(line: &str) -> Result<Self, Self::Err> {
+        // functions declared inside of blocks are usually indented
+        let line = line.trim_start();
+        let (line, is_local) = match line.strip_prefix("local") {
+            Some(rest) if rest.trim_start().starts_with("function") => (rest.trim_start(), true),
+            _ => (line, false),
+        };
         if !line.starts_with("function") {
             return Err(ModdingError::NotALuaFunction(line.to_owned()));
         }
@@ -37,10 +91,17 @@ impl FromStr for LuaFuncDef {
             .next()
             .ok_or(ModdingError::NotALuaFunction(line.to_owned()))?
             .to_owned();
-        let is_baba_native = baba_function_names().contains(&name);
+        let args = line
+            .split_once('(')
+            .and_then(|(_, rest)| rest.split_once(')'))
+            .map(|(args, _)| args.trim().to_owned())
+            .unwrap_or_default();
+        let is_baba_native = !is_local && is_baba_native_name(&name);
         let function = LuaFuncDef {
             name,
             is_baba_native,
+            is_local,
+            args,
         };
         Ok(function)
     }
@@ -1,10 +1,14 @@
-use std::str::FromStr;
+use std::{collections::HashSet, str::FromStr};
 
 use serde::{Deserialize, Serialize};
 
 use crate::error::moddingerror::ModdingError;
 
-use super::{code_to_funcs, luafuncdef::LuaFuncDef};
+use super::{
+    code_to_funcs,
+    luafuncdef::LuaFuncDef,
+    luasyntax::{called_names, replace_identifier_in_code},
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct LuaFunction {
@@ -30,6 +34,29 @@ impl LuaFunction {
     pub fn definition(&self) -> LuaFuncDef {
         self.definition.clone()
     }
+    /// Creates a copy of this function under another name.
+    ///
+    /// Both the definition and any calls the function makes to itself are renamed.
+    /// Only whole identifiers are replaced (so renaming `foo` leaves `foobar` alone),
+    /// and comments and strings are left as they are.
+    pub fn rename(&self, new_name: &str) -> LuaFunction {
+        let name = self.definition.name();
+        LuaFunction {
+            definition: self.definition.renamed(new_name),
+            code: replace_identifier_in_code(&self.code, &name, new_name),
+        }
+    }
+    /// Gets the names of the functions this function calls (see [`called_names`]).
+    ///
+    /// The definition line itself isn't counted, but calls the function makes to itself are.
+    pub fn calls(&self) -> HashSet<String> {
+        let body = self
+            .code
+            .split_once('\n')
+            .map(|(_, body)| body)
+            .unwrap_or_default();
+        called_names(body)
+    }
 }
 
 impl FromStr for LuaFunction {
@@ -80,7 +107,10 @@ impl FromStr for LuaFunction {
             } else {
                 new_code.push_str(line);
             }
+            // keep the line breaks, otherwise e.g. `x = 1` and `end` get glued together into `x = 1end`
+            new_code.push('\n');
         }
+        new_code.pop();
         Ok(Self {
             definition: function,
             code: new_code,
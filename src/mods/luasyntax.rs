@@ -0,0 +1,347 @@
+//! Lightweight helpers for looking at the structure of lua code,
+//! without fully parsing it.
+
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    iter::{once, repeat_n},
+    ops::Range,
+};
+
+/// Lua's reserved words, which can never be the name of a variable or function.
+pub const KEYWORDS: [&str; 22] = [
+    "and", "break", "do", "else", "elseif", "end", "false", "for", "function", "goto", "if", "in",
+    "local", "nil", "not", "or", "repeat", "return", "then", "true", "until", "while",
+];
+
+/// Converts Windows line endings (`\r\n`) into plain line breaks (`\n`).
+///
+/// Lua is split into functions by looking for an unindented `\nend`, which
+/// the `\r` of a Windows line ending would otherwise get in the way of.
+pub fn normalize_line_endings(code: &str) -> String {
+    code.replace("\r\n", "\n")
+}
+
+/// Removes comments and the contents of strings from lua code,
+/// so that keywords inside of them aren't mistaken for code.
+///
+/// Strings are kept as empty quotes (`""`), and line breaks are kept
+/// so that line numbers still line up with the original code.
+pub fn strip_comments_and_strings(code: &str) -> String {
+    let chars: Vec<char> = code.chars().collect();
+    let mut result = String::with_capacity(code.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let char = chars[i];
+        // comments, either `--[[ long ]]` or `-- until the end of the line`
+        if char == '-' && chars.get(i + 1) == Some(&'-') {
+            i += 2;
+            match long_bracket_level(&chars, i) {
+                Some(level) => i = skip_long_bracket(&chars, i, level, &mut result),
+                None => {
+                    while i < chars.len() && chars[i] != '\n' {
+                        i += 1;
+                    }
+                }
+            }
+            continue;
+        }
+        // long strings, `[[ like this ]]`
+        if let Some(level) = long_bracket_level(&chars, i) {
+            result.push_str("\"\"");
+            i = skip_long_bracket(&chars, i, level, &mut result);
+            continue;
+        }
+        // quoted strings, "like this" or 'like this'
+        if char == '"' || char == '\'' {
+            i += 1;
+            while i < chars.len() && chars[i] != char && chars[i] != '\n' {
+                // skip over whatever is escaped
+                if chars[i] == '\\' {
+                    i += 1;
+                }
+                i += 1;
+            }
+            if chars.get(i) == Some(&char) {
+                i += 1;
+            }
+            result.push(char);
+            result.push(char);
+            continue;
+        }
+        result.push(char);
+        i += 1;
+    }
+    result
+}
+
+/// Replaces every use of an identifier in lua code with another.
+///
+/// Unlike [`str::replace`], only whole identifiers are replaced,
+/// so replacing `foo` leaves `foo_left` and `my_foo` alone.
+pub fn replace_identifier(code: &str, from: &str, to: &str) -> String {
+    if from.is_empty() {
+        return code.to_owned();
+    }
+    let is_identifier = |char: char| char.is_alphanumeric() || char == '_';
+    let mut result = String::with_capacity(code.len());
+    let mut rest = code;
+    while let Some(index) = rest.find(from) {
+        let before = rest[..index]
+            .chars()
+            .next_back()
+            .or_else(|| result.chars().next_back());
+        let after = rest[index + from.len()..].chars().next();
+        result.push_str(&rest[..index]);
+        if before.is_some_and(is_identifier) || after.is_some_and(is_identifier) {
+            result.push_str(from);
+        } else {
+            result.push_str(to);
+        }
+        rest = &rest[index + from.len()..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Like [`replace_identifier`], but comments and strings are left untouched,
+/// so renaming `foo` doesn't change `print("foo")` or `-- calls foo`.
+pub fn replace_identifier_in_code(code: &str, from: &str, to: &str) -> String {
+    let mut result = String::with_capacity(code.len());
+    let mut last = 0;
+    for range in literal_ranges(code) {
+        result.push_str(&replace_identifier(&code[last..range.start], from, to));
+        result.push_str(&code[range.clone()]);
+        last = range.end;
+    }
+    result.push_str(&replace_identifier(&code[last..], from, to));
+    result
+}
+
+/// Returns whether a word is a valid lua identifier (e.g. a variable or function name).
+pub fn is_identifier(word: &str) -> bool {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_alphabetic() || first == '_')
+        && chars.all(|char| char.is_alphanumeric() || char == '_')
+}
+
+/// Finds the names of every function called in some lua code, such as `foo` in `foo(1)`
+/// or `table.insert` in `table.insert(t, 1)`.
+///
+/// Method calls (`object:method()`) are left out, since which function they call
+/// depends on the object. Comments and strings are ignored.
+pub fn called_names(code: &str) -> HashSet<String> {
+    let stripped = strip_comments_and_strings(code);
+    let is_name = |char: char| char.is_alphanumeric() || char == '_' || char == '.';
+    let mut result = HashSet::new();
+    let mut rest = stripped.as_str();
+    while let Some(start) = rest.find(is_name) {
+        let before = rest[..start].trim_end().chars().next_back();
+        let end = rest[start..]
+            .find(|char| !is_name(char))
+            .map_or(rest.len(), |end| start + end);
+        let name = &rest[start..end];
+        rest = &rest[end..];
+        let is_call = rest.trim_start().starts_with('(');
+        let is_method = before == Some(':');
+        let is_valid = name.split('.').all(is_identifier) && !is_keyword(name);
+        if is_call && !is_method && is_valid {
+            result.insert(name.to_owned());
+        }
+    }
+    result
+}
+
+/// Returns whether a word is one of lua's [`KEYWORDS`].
+pub fn is_keyword(word: &str) -> bool {
+    KEYWORDS.contains(&word)
+}
+
+/// Reads a line of (stripped) lua code as one name being assigned to another,
+/// such as `local old = init` or `old = init`, returning the assigned name and the value.
+///
+/// Anything else (calls like `foo(init)`, comparisons, multiple assignment, etc.) returns [`None`].
+pub fn simple_assignment(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim().trim_end_matches(';').trim_end();
+    let line = match line.strip_prefix("local") {
+        Some(rest) if rest.starts_with(char::is_whitespace) => rest,
+        _ => line,
+    };
+    let (target, value) = line.split_once('=')?;
+    let (target, value) = (target.trim(), value.trim());
+    (is_identifier(target) && is_identifier(value)).then_some((target, value))
+}
+
+/// Finds where the comments and strings are in lua code (as byte ranges),
+/// i.e. the parts that [`strip_comments_and_strings`] would remove.
+fn literal_ranges(code: &str) -> Vec<Range<usize>> {
+    let chars: Vec<char> = code.chars().collect();
+    let offsets: Vec<usize> = code
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain(once(code.len()))
+        .collect();
+    // the line breaks pushed by `skip_long_bracket` aren't needed here
+    let mut discarded = String::new();
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i;
+        let char = chars[i];
+        if char == '-' && chars.get(i + 1) == Some(&'-') {
+            i += 2;
+            match long_bracket_level(&chars, i) {
+                Some(level) => i = skip_long_bracket(&chars, i, level, &mut discarded),
+                None => {
+                    while i < chars.len() && chars[i] != '\n' {
+                        i += 1;
+                    }
+                }
+            }
+        } else if let Some(level) = long_bracket_level(&chars, i) {
+            i = skip_long_bracket(&chars, i, level, &mut discarded);
+        } else if char == '"' || char == '\'' {
+            i += 1;
+            while i < chars.len() && chars[i] != char && chars[i] != '\n' {
+                if chars[i] == '\\' {
+                    i += 1;
+                }
+                i += 1;
+            }
+            if chars.get(i) == Some(&char) {
+                i += 1;
+            }
+        } else {
+            i += 1;
+            continue;
+        }
+        i = i.min(chars.len());
+        ranges.push(offsets[start]..offsets[i]);
+    }
+    ranges
+}
+
+/// If a long bracket (`[[`, `[=[`, `[==[`, etc.) opens at `start`, returns its level (the number of `=`s).
+fn long_bracket_level(chars: &[char], start: usize) -> Option<usize> {
+    if chars.get(start) != Some(&'[') {
+        return None;
+    }
+    let level = chars[start + 1..]
+        .iter()
+        .take_while(|&&char| char == '=')
+        .count();
+    (chars.get(start + 1 + level) == Some(&'[')).then_some(level)
+}
+
+/// Skips past a long bracket opening at `start`, returning the index just after it closes.
+///
+/// Any line breaks inside of the brackets are pushed onto `result`.
+fn skip_long_bracket(chars: &[char], start: usize, level: usize, result: &mut String) -> usize {
+    let close: Vec<char> = once(']')
+        .chain(repeat_n('=', level))
+        .chain(once(']'))
+        .collect();
+    let mut i = start + level + 2;
+    while i < chars.len() {
+        if chars[i..].starts_with(&close) {
+            return i + close.len();
+        }
+        if chars[i] == '\n' {
+            result.push('\n');
+        }
+        i += 1;
+    }
+    i
+}
+
+/// Where (and why) some lua code is not properly structured, see [`validate_structure`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructureError {
+    /// The line the problem was found on (starting from 1)
+    pub line: usize,
+    /// What went wrong
+    pub reason: String,
+}
+
+impl Display for StructureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+/// Checks that the blocks in some lua code are balanced.
+///
+/// Every `function`, `do` (which covers `while` and `for`), and `if` needs a matching `end`,
+/// every `repeat` needs a matching `until`, and every `(` needs a matching `)`.
+/// This doesn't check that the code is valid lua, only that it isn't obviously broken.
+///
+/// # Errors
+/// Returns the first place the structure was found to be broken.
+pub fn validate_structure(code: &str) -> Result<(), StructureError> {
+    // each open block, with the line it was opened on
+    let mut open: Vec<(&str, usize)> = Vec::new();
+    let stripped = strip_comments_and_strings(code);
+    for (index, line) in stripped.lines().enumerate() {
+        let line_number = index + 1;
+        let error = |reason: String| StructureError {
+            line: line_number,
+            reason,
+        };
+        for token in tokens(line) {
+            match token {
+                "function" | "do" | "if" | "repeat" | "(" => open.push((token, line_number)),
+                "end" | "until" | ")" => {
+                    let expected: &[&str] = match token {
+                        "end" => &["function", "do", "if"],
+                        "until" => &["repeat"],
+                        _ => &["("],
+                    };
+                    match open.pop() {
+                        Some((opener, _)) if expected.contains(&opener) => {}
+                        Some((opener, opened_on)) => {
+                            return Err(error(format!(
+                                "found `{}`, but the `{}` on line {} is still open",
+                                token, opener, opened_on
+                            )))
+                        }
+                        None => return Err(error(format!("found an unexpected `{}`", token))),
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    match open.pop() {
+        Some((opener, opened_on)) => Err(StructureError {
+            line: opened_on,
+            reason: format!("`{}` is never closed", opener),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Splits a line of (stripped) lua code into words and parentheses, dropping everything else.
+fn tokens(line: &str) -> Vec<&str> {
+    let mut result = Vec::new();
+    let mut word_start = None;
+    for (i, char) in line.char_indices() {
+        let is_word = char.is_alphanumeric() || char == '_';
+        match (is_word, word_start) {
+            (true, None) => word_start = Some(i),
+            (false, Some(start)) => {
+                result.push(&line[start..i]);
+                word_start = None;
+            }
+            _ => {}
+        }
+        if char == '(' || char == ')' {
+            result.push(&line[i..i + 1]);
+        }
+    }
+    if let Some(start) = word_start {
+        result.push(&line[start..]);
+    }
+    result
+}
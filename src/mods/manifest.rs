@@ -0,0 +1,81 @@
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Every file belonging to a mod (by its path inside of the mod's archive, e.g. `my_mod/Config.json`),
+/// alongside the SHA-256 digest of its contents as lowercase hex.
+///
+/// Created via [`crate::mods::babamod::BabaMod::manifest`].
+pub type Manifest = BTreeMap<PathBuf, String>;
+
+/// The differences between a mod's files and a [`Manifest`] of what they should be.
+///
+/// Created via [`crate::mods::babamod::BabaMod::verify_against`].
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestDiff {
+    /// Files that exist, but aren't in the manifest (sorted)
+    pub added: Vec<PathBuf>,
+    /// Files in the manifest that don't exist (sorted)
+    pub removed: Vec<PathBuf>,
+    /// Files whose contents don't match the manifest (sorted)
+    pub changed: Vec<PathBuf>,
+}
+
+impl ManifestDiff {
+    /// Compares what the files should be (`expected`) against what they are (`actual`).
+    pub fn between(expected: &Manifest, actual: &Manifest) -> Self {
+        let added = actual
+            .keys()
+            .filter(|path| !expected.contains_key(*path))
+            .cloned()
+            .collect();
+        let mut removed = Vec::new();
+        let mut changed = Vec::new();
+        for (path, digest) in expected {
+            match actual.get(path) {
+                None => removed.push(path.clone()),
+                Some(actual) if actual != digest => changed.push(path.clone()),
+                Some(_) => {}
+            }
+        }
+        Self {
+            added,
+            removed,
+            changed,
+        }
+    }
+
+    /// Returns whether the files match the manifest exactly.
+    pub fn is_unchanged(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Digests some bytes with SHA-256, returning it as lowercase hex.
+pub fn sha256_hex(bytes: impl AsRef<[u8]>) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Adds a file (or every file inside of a directory) to a [`Manifest`], under the given name.
+///
+/// Names are always separated with `/`, matching [`crate::files::zip_recursively`].
+pub fn add_to_manifest(manifest: &mut Manifest, from: &Path, name: &str) -> Result<(), io::Error> {
+    if from.is_dir() {
+        for entry in from.read_dir()? {
+            let entry = entry?;
+            let child = format!("{}/{}", name, entry.file_name().to_string_lossy());
+            add_to_manifest(manifest, &entry.path(), &child)?;
+        }
+    } else {
+        manifest.insert(PathBuf::from(name), sha256_hex(fs::read(from)?));
+    }
+    Ok(())
+}
@@ -1,59 +1,249 @@
-use std::{collections::HashSet, ffi::OsStr, path::Path, str::FromStr};
+use std::{
+    collections::HashSet,
+    ffi::OsStr,
+    fs,
+    io::{BufRead, Read},
+    path::Path,
+    str::{self, FromStr},
+    sync::OnceLock,
+};
 
+use babamod::BabaMod;
 use luafuncdef::LuaFuncDef;
 use luafunction::LuaFunction;
+use luasyntax::normalize_line_endings;
+
+use crate::{error::babaerror::BabaError, files::native_file_stem};
 
 pub mod babamod;
+pub mod compatibility;
 pub mod config;
 pub mod luafuncdef;
 pub mod luafunction;
+pub mod luasyntax;
+pub mod manifest;
+pub mod modkind;
+
+/// How many folders up from a mod to look for a `Sprites` folder.
+///
+/// A mod in a levelpack lives at `[pack]\Lua\[mod]`, so the sprites
+/// folder should be found within a couple parents at most.
+pub const SPRITES_SEARCH_DEPTH: usize = 3;
+
+/// The name of the folder (inside the system's temp directory)
+/// that mod archives are extracted into.
+pub const ARCHIVE_EXTRACTION_DIR: &str = "babamodmanager_archives";
 
 /// Returns whether or not the [`PathBuf`] is a lua file
 pub fn is_lua_file(path: &Path) -> bool {
     path.extension().map(OsStr::to_os_string) == Some("lua".into())
 }
 
+/// How many bytes from the start of a file [`is_probably_text`] looks at.
+pub const TEXT_SNIFF_LENGTH: usize = 1024;
+
+/// Returns whether a file looks like text, judging by its first [`TEXT_SNIFF_LENGTH`] bytes.
+///
+/// Text has no null bytes and is valid UTF-8 (allowing for a character cut off at the end).
+/// Files that can't be read at all don't count as text.
+pub fn is_probably_text(path: &Path) -> bool {
+    let Ok(file) = fs::File::open(path) else {
+        return false;
+    };
+    let mut start = Vec::with_capacity(TEXT_SNIFF_LENGTH);
+    if file
+        .take(TEXT_SNIFF_LENGTH as u64)
+        .read_to_end(&mut start)
+        .is_err()
+    {
+        return false;
+    }
+    if start.contains(&0) {
+        return false;
+    }
+    match str::from_utf8(&start) {
+        Ok(_) => true,
+        // the error has no length if the text was only cut off partway through a character
+        Err(error) => error.error_len().is_none(),
+    }
+}
+
+/// Returns whether an entry in a config's `files` is a glob pattern (e.g. `sounds/*.ogg`)
+/// rather than a plain path.
+pub fn is_glob_pattern(entry: &str) -> bool {
+    entry.contains(['*', '?', '['])
+}
+
+/// Returns whether a sprite file (by its file name) belongs to a sprite declared by a mod.
+///
+/// The names are compared exactly (see [`sprite_base_name`]), so declaring `baba`
+/// matches `baba_0_1.png`, but not `megababa_0_1.png` or `text_baba_0_1.png`.
+pub fn sprite_matches(declared: &str, file_name: &str) -> bool {
+    sprite_base_name(file_name) == declared
+}
+
+/// Gets the name of the sprite a sprite file is for.
+///
+/// Sprite files are named `[name]_[variant]_[wobble].png`, so the extension and any
+/// trailing numbers are removed, e.g. `baba_0_1.png` and `text_baba_0_1.png`
+/// become `baba` and `text_baba` respectively.
+pub fn sprite_base_name(file_name: &str) -> &str {
+    let mut name = Path::new(file_name)
+        .file_stem()
+        .and_then(OsStr::to_str)
+        .unwrap_or(file_name);
+    while let Some((rest, number)) = name.rsplit_once('_') {
+        if rest.is_empty() || number.is_empty() || !number.chars().all(|char| char.is_ascii_digit())
+        {
+            break;
+        }
+        name = rest;
+    }
+    name
+}
+
+/// Creates a [`BabaMod`] for every mod in a directory of mods (i.e. a `Lua` folder).
+///
+/// Each folder is a mod, and so is each loose `.lua` file (as a singleton), except for:
+/// - Files that replace a native baba file (see [`native_file_stem`]), since they aren't mods in their own right
+/// - The init files of folder mods, which sit next to the folder
+/// - Anything else, such as a readme
+///
+/// # Errors
+/// This function may error if the directory could not be read ([`std::io::Error`])
+pub fn mods_in_directory(dir: &Path) -> Result<Vec<BabaMod>, BabaError> {
+    let mut result = Vec::new();
+    for entry in dir.read_dir()?.flatten() {
+        let path = entry.path();
+        if path.is_dir() || (is_lua_file(&path) && native_file_stem(&path).is_none()) {
+            result.push(BabaMod::new(path));
+        }
+    }
+    let inits: Vec<String> = result
+        .iter()
+        .filter(|baba_mod| !baba_mod.is_singleton())
+        .filter_map(|baba_mod| init_file_name(&baba_mod.init_path()?))
+        .collect();
+    result.retain(|baba_mod| {
+        !baba_mod.is_singleton()
+            || !init_file_name(baba_mod.path()).is_some_and(|name| inits.contains(&name))
+    });
+    Ok(result)
+}
+
+/// Gets the (lowercase) file name of an init file, whichever separator its path was written with
+/// (configs usually give it as `.\[mod]_init.lua`).
+fn init_file_name(path: &Path) -> Option<String> {
+    let path = path.to_str()?;
+    path.rsplit(['/', '\\'])
+        .next()
+        .map(|name| name.to_lowercase())
+}
+
 /// Procures a set of [`LuaFuncDef`]s from a string.
 ///
 /// This is only the definitions and related data, everything else in the
 /// string is ignored
 pub fn functions_from_string(str: &str) -> HashSet<LuaFuncDef> {
+    functions_from_reader(str.as_bytes())
+}
+
+/// Procures a set of [`LuaFuncDef`]s from a reader, such as a [`std::io::BufReader`] over a file.
+///
+/// Like [`functions_from_string`], but only a line is held at a time, so large files are never
+/// read into memory all at once. Reading stops at the first error, and any text that isn't
+/// valid UTF-8 is replaced (see [`String::from_utf8_lossy`]).
+pub fn functions_from_reader(mut reader: impl BufRead) -> HashSet<LuaFuncDef> {
     let mut result = HashSet::new();
-    for line in str.lines() {
-        let Ok(function) = line.parse() else {
-            continue;
-        };
-        result.insert(function);
+    let mut line = Vec::new();
+    while let Ok(read) = reader.read_until(b'\n', &mut line) {
+        if read == 0 {
+            break;
+        }
+        if let Ok(function) = String::from_utf8_lossy(&line).trim_end().parse() {
+            result.insert(function);
+        }
+        line.clear();
     }
     result
 }
 
-pub fn baba_function_names() -> HashSet<String> {
-    include_str!("../data/babafuncs.txt")
-        .split('\n')
-        .map(ToOwned::to_owned)
-        .collect()
+/// The names of every function baba defines itself (listed in `data/babafuncs.txt`).
+///
+/// The set is only built the first time it's needed, every call after that returns the same set.
+pub fn baba_function_names() -> &'static HashSet<String> {
+    static NAMES: OnceLock<HashSet<String>> = OnceLock::new();
+    NAMES.get_or_init(|| {
+        include_str!("../data/babafuncs.txt")
+            .lines()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(ToOwned::to_owned)
+            .collect()
+    })
+}
+
+/// Returns whether a function with this name is defined by baba itself, see [`baba_function_names`].
+pub fn is_baba_native_name(name: &str) -> bool {
+    baba_function_names().contains(name)
+}
+
+/// Tidies up a list of author names.
+///
+/// Entries holding several names (e.g. `"Alice, Bob"`) are split apart, whitespace and
+/// surrounding quotes are trimmed off, and empty or repeated names (ignoring case) are dropped.
+/// The first spelling of each name is kept, in the order they were given.
+pub fn clean_author_names(authors: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut result: Vec<String> = Vec::new();
+    for entry in authors {
+        for name in entry.split(',') {
+            let name = name.trim().trim_matches(['"', '\'']).trim();
+            let is_repeat = result
+                .iter()
+                .any(|existing| existing.eq_ignore_ascii_case(name));
+            if !name.is_empty() && !is_repeat {
+                result.push(name.to_owned());
+            }
+        }
+    }
+    result
 }
 
 /// Splits a string into a set of Lua functions (also as Strings).
 ///
 /// This discards any extraneous data, only containing the functions.
+/// Windows line endings are converted first (see [`normalize_line_endings`]).
 pub fn code_to_funcs(file: &str) -> Vec<LuaFunction> {
+    // `\r\nend` would never be split at below
+    let file = normalize_line_endings(file);
     // Split the string at every use of `function`
-    file.split("function")
-        // split it again at every `end` without indentation,
-        // then grab the first part (so before the end)
-        .map(|x| x.split("\nend").next())
-        // we should have at least something before the end
-        // so this is just type casting from
-        // Option<&str> -> &str
-        .map(Option::unwrap_or_default)
-        // &str -> String
-        .map(ToOwned::to_owned)
-        // puts the `function` back on the front of the string
-        .map(|str| concat_strings("function".to_owned(), str))
-        // puts the `end` on the back of the string
-        .map(|str| concat_strings(str, "\nend".to_owned()))
+    let pieces: Vec<&str> = file.split("function").collect();
+    pieces
+        .iter()
+        .enumerate()
+        // `local function` leaves the `local` at the end of the previous piece,
+        // so it needs to be put back as well
+        .map(|(i, x)| {
+            let is_local = i > 0 && pieces[i - 1].split_whitespace().last() == Some("local");
+            (is_local, x)
+        })
+        // split it again at the first `end` without indentation,
+        // then grab the first part (so before the end), remembering whether there was an end at all
+        .map(|(is_local, x)| match x.split_once("\nend") {
+            Some((before, _)) => (is_local, before, true),
+            None => (is_local, *x, false),
+        })
+        // puts the `function` (and `local`, if needed) back on the front of the string
+        .map(|(is_local, str, had_end)| match is_local {
+            true => (format!("local function{}", str), had_end),
+            false => (format!("function{}", str), had_end),
+        })
+        // puts the `end` back on the string (exactly as it was split off, line break included),
+        // but only if one was taken off (e.g. a one line function, or code after the last function, never had one)
+        .map(|(str, had_end)| match had_end {
+            true => concat_strings(str, "end".to_owned()),
+            false => str,
+        })
         // String -> Result<LuaFunction, Error>
         .flat_map(|arg0| LuaFunction::from_str(&arg0))
         // collect it into a list
@@ -0,0 +1,26 @@
+use std::path::Path;
+
+/// What a mod is made of on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ModKind {
+    /// A folder holding the mod's files (and usually a config)
+    Directory,
+    /// A standalone lua file, also known as a singleton
+    File,
+}
+
+impl ModKind {
+    /// Works out the kind of mod at a path by looking at what is on disk.
+    ///
+    /// If nothing exists at the path yet, a path with an extension is taken to be a file,
+    /// and anything else a directory.
+    pub fn of(path: &Path) -> Self {
+        if path.is_dir() {
+            Self::Directory
+        } else if path.is_file() || path.extension().is_some() {
+            Self::File
+        } else {
+            Self::Directory
+        }
+    }
+}
@@ -1,6 +1,112 @@
 #![cfg(test)]
 
-use crate::{application::{icon, load_fonts, load_themes, pixel_index}, files::babafiles::BabaFiles, levelpack::fetch_field as ff, mods::config::Config};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::PathBuf,
+};
+
+use crate::{
+    command::{merge_paths, Command},
+    error::{
+        babaerror::BabaError, commanderror::CommandError, installerror::InstallError,
+        moddingerror::ModdingError,
+    },
+    files::{
+        babafiles::BabaFiles, fetch::store_in_cache, luafile::LuaFile, temporary_path,
+        writeinto::WriteInto, BABA_LUA_FILE_NAMES,
+    },
+    levelpack::{
+        fetch_all_fields, fetch_field as ff, levelentry::LevelEntry, levelpackfile::LevelpackFile,
+        levelpackrepr::LevelpackRepr,
+    },
+    merge::{
+        injection_wrapper, merge_files, merge_files_diff, merge_many, merge_many_with_progress,
+        merged_mod_id, mergeoptions::MergeOptions, mergeprogress::MergeProgress,
+        MAX_MERGED_ID_LENGTH,
+    },
+    mods::{
+        baba_function_names,
+        babamod::BabaMod,
+        code_to_funcs,
+        config::{Config, ConfigIssue},
+        functions_from_reader, functions_from_string, is_baba_native_name, is_probably_text,
+        luafuncdef::LuaFuncDef,
+        luafunction::LuaFunction,
+        luasyntax::validate_structure,
+        manifest::{add_to_manifest, Manifest},
+        modkind::ModKind,
+        sprite_base_name, sprite_matches,
+    },
+};
+
+/// Creates a fresh, empty directory in the system's temp folder for a test to work in
+fn scratch_dir(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join("babamodmanager_tests").join(name);
+    let _ = fs::remove_dir_all(&path);
+    fs::create_dir_all(&path).unwrap();
+    path
+}
+
+/// Creates the contents of a `Config.json` with the given mod id and sprites
+fn config_json(modid: &str, sprites: &[&str]) -> String {
+    config_with(modid, sprites, serde_json::json!({})).to_string()
+}
+
+/// Creates the json of a `Config.json` with the given mod id and sprites,
+/// with any fields in `overrides` replacing the defaults
+fn config_with(modid: &str, sprites: &[&str], overrides: serde_json::Value) -> serde_json::Value {
+    let mut config = serde_json::json!({
+        "modid": modid,
+        "authors": ["Author A"],
+        "description": "A mod used for testing",
+        "icon_url": null,
+        "banner_url": null,
+        "global": false,
+        "tags": [],
+        "links": [],
+        "files": [],
+        "init": null,
+        "sprites": sprites
+    });
+    if let serde_json::Value::Object(overrides) = overrides {
+        for (field, value) in overrides {
+            config[field] = value;
+        }
+    }
+    config
+}
+
+/// Creates a levelpack folder with the given name and `world_data.txt` contents
+fn fixture_pack(root: &std::path::Path, folder: &str, world_data: &str) -> PathBuf {
+    let path = root.join(folder);
+    fs::create_dir_all(&path).unwrap();
+    fs::write(path.join("world_data.txt"), world_data).unwrap();
+    path
+}
+
+/// Creates a mod folder holding a config and a single lua file (listed in the config), returning its path
+fn fixture_mod(root: &std::path::Path, id: &str, code: &str) -> PathBuf {
+    fixture_mod_with(root, id, code, serde_json::json!({}))
+}
+
+/// Like [`fixture_mod`], with any fields in `overrides` replacing the config's defaults
+fn fixture_mod_with(
+    root: &std::path::Path,
+    id: &str,
+    code: &str,
+    mut overrides: serde_json::Value,
+) -> PathBuf {
+    let path = root.join(id);
+    fs::create_dir_all(&path).unwrap();
+    if overrides.get("files").is_none() {
+        overrides["files"] = serde_json::json!([format!("{}.lua", id)]);
+    }
+    let config = config_with(id, &[], overrides);
+    fs::write(path.join("Config.json"), config.to_string()).unwrap();
+    fs::write(path.join(format!("{}.lua", id)), code).unwrap();
+    path
+}
 
 /// Tests whether or not `fetch_field` returns an `Ok` variant
 #[test]
@@ -17,6 +123,15 @@ fn fetch_field_2() {
     assert_eq!(x, "abc");
 }
 
+/// Tests whether or not `fetch_field` ignores whitespace around the `=`
+#[test]
+fn fetch_field_3() {
+    for line in ["name =abc", "name= abc", "name = abc"] {
+        let x: Result<String, _> = ff("name", line);
+        assert_eq!(x.unwrap(), "abc");
+    }
+}
+
 #[test]
 fn find_baba_files() {
     let x = BabaFiles::from_steam();
@@ -78,47 +193,2116 @@ fn can_parse_config() {
     assert!(value.is_ok(), "{:?}", value.err().unwrap());
 }
 
+/// Tests whether or not `levelpacks_verbose` returns the packs that failed to load alongside the ones that didn't
+#[test]
+fn levelpacks_verbose_reports_failures() {
+    let root = scratch_dir("levelpacks_verbose_reports_failures");
+    let worlds = root.join("Data").join("Worlds");
+    fixture_pack(&worlds, "good", "[general]\nname=Good\n");
+    // a pack without a world_data.txt can't be loaded
+    fs::create_dir_all(worlds.join("broken")).unwrap();
+    let files = BabaFiles::from_raw(root);
+    let (packs, failures) = files.levelpacks_verbose(false).unwrap();
+    assert_eq!(packs.len(), 1);
+    assert_eq!(failures.len(), 1);
+    assert!(failures[0].0.ends_with("broken"), "{:?}", failures);
+}
+
+/// Tests whether both folder and singleton mods use the `Sprites` folder of their levelpack
+#[test]
+fn sprites_folder_is_the_packs() {
+    let root = scratch_dir("sprites_folder_is_the_packs");
+    let pack = fixture_pack(&root, "pack", "[general]\nname=Pack\n");
+    fs::create_dir_all(pack.join("Sprites")).unwrap();
+    fs::create_dir_all(pack.join("Lua").join("mymod")).unwrap();
+    fs::write(pack.join("Lua").join("mymod.lua"), "").unwrap();
+    for path in [
+        pack.join("Lua").join("mymod"),
+        pack.join("Lua").join("mymod.lua"),
+    ] {
+        let baba_mod = BabaMod::new(path);
+        assert_eq!(baba_mod.sprites_folder().unwrap(), pack.join("Sprites"));
+    }
+}
+
+/// Tests whether two mods overriding the same native function are reported as incompatible
+#[test]
+fn compatibility_lists_shared_native_override() {
+    let root = scratch_dir("compatibility_lists_shared_native_override");
+    let left = root.join("left.lua");
+    let right = root.join("right.lua");
+    fs::write(&left, "function init()\nend\nfunction left_only()\nend\n").unwrap();
+    fs::write(&right, "function init()\nend\nfunction right_only()\nend\n").unwrap();
+    let left = BabaMod::new(left);
+    let right = BabaMod::new(right);
+    let compatibility = left.compatibility_with(&right);
+    assert!(!left.is_compatible_with(&right));
+    let names: Vec<_> = compatibility
+        .conflicting_functions()
+        .iter()
+        .map(|overlap| overlap.definition.name())
+        .collect();
+    assert_eq!(names, vec!["init".to_owned()]);
+    assert!(compatibility.conflicting_sprites().is_empty());
+}
+
+/// Tests whether shared native functions are mergeable, while other shared functions are hard conflicts
+#[test]
+fn compatibility_classifies_overlaps() {
+    let root = scratch_dir("compatibility_classifies_overlaps");
+    let left = root.join("left.lua");
+    let right = root.join("right.lua");
+    fs::write(&left, "function init()\nend\nfunction helper()\nend\n").unwrap();
+    fs::write(&right, "function init()\nend\nfunction helper()\nend\n").unwrap();
+    let compatibility = BabaMod::new(left).compatibility_with(&BabaMod::new(right));
+    let mergeable: Vec<_> = compatibility
+        .mergeable_overlaps()
+        .map(|overlap| overlap.definition.name())
+        .collect();
+    let conflicts: Vec<_> = compatibility
+        .hard_conflicts()
+        .map(|overlap| overlap.definition.name())
+        .collect();
+    assert_eq!(mergeable, vec!["init".to_owned()]);
+    assert_eq!(conflicts, vec!["helper".to_owned()]);
+    assert!(!compatibility.is_mergeable());
+}
+
+/// Tests whether a mod's functions are only read again once its cache is invalidated
+#[test]
+fn mod_scans_are_cached() {
+    let root = scratch_dir("mod_scans_are_cached");
+    let path = root.join("cached.lua");
+    fs::write(&path, "function first()\nend\n").unwrap();
+    let baba_mod = BabaMod::new(path.clone());
+    let before = baba_mod.defined_function_definitions();
+    // changing the file on disk shouldn't be noticed until the cache is invalidated
+    fs::write(&path, "function second()\nend\n").unwrap();
+    assert_eq!(baba_mod.defined_function_definitions(), before);
+    baba_mod.invalidate_cache();
+    let after = baba_mod.defined_function_definitions();
+    assert_ne!(after, before);
+    assert!(after.iter().any(|func| func.name() == "second"));
+}
+
+/// Tests whether baba's own lua files aren't mistaken for global mods
+#[test]
+fn global_mods_exclude_native_files() {
+    let root = scratch_dir("global_mods_exclude_native_files");
+    let lua = root.join("Lua");
+    fs::create_dir_all(lua.join("foldermod")).unwrap();
+    fs::write(lua.join("movement.lua"), "").unwrap();
+    fs::write(lua.join("filemod.lua"), "").unwrap();
+    let files = BabaFiles::from_raw(root);
+    let mut names: Vec<_> = files
+        .global_mods()
+        .unwrap()
+        .iter()
+        .map(BabaMod::name)
+        .collect();
+    names.sort();
+    assert_eq!(
+        names,
+        vec!["filemod.lua".to_owned(), "foldermod".to_owned()]
+    );
+}
+
+/// Tests whether a singleton mod only overrides a native file when it has the exact same name
+#[test]
+fn singletons_override_native_files_by_name() {
+    let root = scratch_dir("singletons_override_native_files_by_name");
+    fs::write(root.join("movement.lua"), "").unwrap();
+    fs::write(root.join("my_movement.lua"), "").unwrap();
+    let baba_mod = BabaMod::new(root.join("movement.lua"));
+    assert_eq!(
+        baba_mod.overrides_native_file(),
+        Some("movement".to_owned())
+    );
+    let baba_mod = BabaMod::new(root.join("my_movement.lua"));
+    assert_eq!(baba_mod.overrides_native_file(), None);
+}
+
+/// Tests whether a mod can be loaded from a `.zip` archive
+#[test]
+fn mod_from_archive() {
+    use std::io::{Cursor, Write};
+    use zip::{write::SimpleFileOptions, ZipWriter};
+
+    let root = scratch_dir("mod_from_archive");
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default();
+    writer.start_file("archived/Config.json", options).unwrap();
+    writer
+        .write_all(config_json("archived_mod", &[]).as_bytes())
+        .unwrap();
+    writer.start_file("archived/archived.lua", options).unwrap();
+    writer.write_all(b"function archived()\nend\n").unwrap();
+    let bytes = writer.finish().unwrap().into_inner();
+    let archive = root.join("archived.zip");
+    fs::write(&archive, bytes).unwrap();
+
+    let baba_mod = BabaMod::from_archive(&archive).unwrap();
+    assert_eq!(baba_mod.mod_id(), "archived_mod");
+    assert!(baba_mod.has_config());
+}
+
+/// Tests whether installing a mod that conflicts with an installed mod leaves the pack untouched
+#[test]
+fn install_mod_aborts_on_conflict() {
+    let root = scratch_dir("install_mod_aborts_on_conflict");
+    let pack_path = fixture_pack(&root, "pack", "[general]\nname=Pack\nmods=1\n");
+    fs::create_dir_all(pack_path.join("Lua")).unwrap();
+    fs::write(
+        pack_path.join("Lua").join("existing.lua"),
+        "function init()\nend\n",
+    )
+    .unwrap();
+    fs::write(root.join("incoming.lua"), "function init()\nend\n").unwrap();
+
+    let pack = LevelpackRepr::new(pack_path.clone()).unwrap();
+    let files = BabaFiles::from_raw(root.clone());
+    let result = pack.install_mod(&BabaMod::new(root.join("incoming.lua")), &files);
+    assert!(
+        matches!(
+            result,
+            Err(BabaError::Modding(ModdingError::InstallConflict(ref clashes))) if *clashes == vec!["existing.lua".to_owned()]
+        ),
+        "{:?}",
+        result
+    );
+    assert!(!pack_path.join("Lua").join("incoming.lua").exists());
+}
+
+/// Tests whether uninstalling a mod keeps the sprites other mods still declare
+#[test]
+fn uninstall_keeps_shared_sprites() {
+    let root = scratch_dir("uninstall_keeps_shared_sprites");
+    let pack_path = fixture_pack(&root, "pack", "[general]\nname=Pack\nmods=1\n");
+    let sprites = pack_path.join("Sprites");
+    fs::create_dir_all(&sprites).unwrap();
+    fs::write(sprites.join("rock_0_1.png"), "").unwrap();
+    fs::write(sprites.join("pebble_0_1.png"), "").unwrap();
+    for (folder, declared) in [("first", vec!["rock", "pebble"]), ("second", vec!["rock"])] {
+        let path = pack_path.join("Lua").join(folder);
+        fs::create_dir_all(&path).unwrap();
+        fs::write(path.join("Config.json"), config_json(folder, &declared)).unwrap();
+    }
+
+    let pack = LevelpackRepr::new(pack_path.clone()).unwrap();
+    pack.uninstall_mod(&BabaMod::new(pack_path.join("Lua").join("first")))
+        .unwrap();
+    assert!(!pack_path.join("Lua").join("first").exists());
+    assert!(pack_path.join("Lua").join("second").exists());
+    assert!(sprites.join("rock_0_1.png").exists());
+    assert!(!sprites.join("pebble_0_1.png").exists());
+}
+
+/// Tests whether the arguments of a function definition are read
+#[test]
+fn function_definition_arguments() {
+    let definition: LuaFuncDef = "function foo(a, b, c)".parse().unwrap();
+    assert_eq!(definition.name(), "foo");
+    assert_eq!(definition.args(), "a, b, c");
+    let no_args: LuaFuncDef = "function bar()".parse().unwrap();
+    assert_eq!(no_args.args(), "");
+}
+
+/// Tests whether an injected function calls the original with its own arguments, exactly once
+#[test]
+fn injection_wrapper_forwards_arguments() {
+    let injected: LuaFunction = "function init(a, b, c)\n\tdo_something()\nend"
+        .parse()
+        .unwrap();
+    let wrapper = injection_wrapper(&injected, "oldinit");
+    assert!(wrapper.contains("oldinit(a, b, c)"));
+    assert!(wrapper.trim_end().ends_with("end"));
+
+    let already_forwarding: LuaFunction = "function init(a, b, c)\n\toldinit(a, b, c)\nend"
+        .parse()
+        .unwrap();
+    let wrapper = injection_wrapper(&already_forwarding, "oldinit");
+    assert_eq!(wrapper.matches("oldinit(").count(), 1);
+}
+
+/// Tests whether the forwarding call of an injected function runs before it returns,
+/// and isn't mistaken for a call to a function that only ends with the same name
+#[test]
+fn injection_wrapper_forwards_before_returning() {
+    let returning: LuaFunction = "function init(a, b)\n\tlocal c = a + b\n\treturn 2\nend"
+        .parse()
+        .unwrap();
+    let wrapper = injection_wrapper(&returning, "oldinit");
+    let call = wrapper.find("oldinit(a, b)").unwrap();
+    assert!(call < wrapper.find("return 2").unwrap(), "{}", wrapper);
+
+    let lookalike: LuaFunction = "function init(a)\n\tmyoldinit(a) -- end\nend"
+        .parse()
+        .unwrap();
+    let wrapper = injection_wrapper(&lookalike, "oldinit");
+    assert_eq!(wrapper.matches("\toldinit(a)").count(), 1, "{}", wrapper);
+
+    let left = LuaFile::from("local oldinit = init\nfunction init(a) print(a) return a end");
+    let right = LuaFile::from("function init(a)\n\tright(a)\nend");
+    let merged = merge_files(left, right, &[]).unwrap().code();
+    let call = merged.find("oldinit(a)").unwrap();
+    assert!(call < merged.find("return a").unwrap(), "{}", merged);
+}
+
+/// Tests whether merging collapses runs of blank lines, but keeps single ones
+#[test]
+fn merging_keeps_single_blank_lines() {
+    let left = LuaFile::from("function a()\nend\n\nfunction b()\nend\n\n\n\n");
+    let right = LuaFile::from("function c()\nend");
+    let merged = merge_files(left, right, &[]).unwrap().code();
+    assert!(merged.contains("end\n\nfunction b()"));
+    assert!(!merged.contains("\n\n\n"));
+}
+
+/// Tests whether a lua file that isn't UTF-8 is reported as such
+#[test]
+fn non_utf8_lua_file() {
+    let root = scratch_dir("non_utf8_lua_file");
+    let path = root.join("latin1.lua");
+    // "caf\xe9" in Latin-1
+    fs::write(&path, b"-- caf\xe9\nfunction a()\nend").unwrap();
+    let result = LuaFile::try_from(path);
+    assert!(matches!(
+        result,
+        Err(BabaError::Modding(ModdingError::NonUtf8Lua(_)))
+    ));
+}
+
+/// Tests whether indented function definitions are still found
+#[test]
+fn indented_function_definition() {
+    let definition: LuaFuncDef = "    function foo()".parse().unwrap();
+    assert_eq!(definition.name(), "foo");
+    let functions = functions_from_string("do\n\tfunction bar(x)\n\tend\nend");
+    assert!(functions.iter().any(|function| function.name() == "bar"));
+}
+
+/// Tests whether `local function` definitions are read, and never count as native
+#[test]
+fn local_function_definition() {
+    let definition: LuaFuncDef = "local function helper(x)".parse().unwrap();
+    assert_eq!(definition.name(), "helper");
+    assert_eq!(definition.args(), "x");
+    assert!(definition.is_local());
+    assert!(!definition.is_baba_native());
+}
+
+/// Tests whether a merge only replaces an existing mod when asked to overwrite it
+#[test]
+fn merge_target_overwrite() {
+    let root = scratch_dir("merge_target_overwrite");
+    let config = || serde_json::from_str::<Config>(&config_json("merged", &[])).unwrap();
+    let options = |overwrite| MergeOptions {
+        location: root.clone(),
+        overwrite,
+        ..Default::default()
+    };
+    BabaMod::init_with_options(LuaFile::from(""), root.clone(), config(), options(false)).unwrap();
+    fs::write(root.join("merged").join("stale.lua"), "").unwrap();
+
+    let refused =
+        BabaMod::init_with_options(LuaFile::from(""), root.clone(), config(), options(false));
+    assert!(matches!(
+        refused,
+        Err(BabaError::Modding(ModdingError::MergeTargetExists(_)))
+    ));
+    assert!(root.join("merged").join("stale.lua").exists());
+
+    BabaMod::init_with_options(LuaFile::from(""), root.clone(), config(), options(true)).unwrap();
+    assert!(!root.join("merged").join("stale.lua").exists());
+    assert!(root.join("merged").join("merged.lua").exists());
+}
+
+/// Tests whether a new mod can be created in folders that don't exist yet
+#[test]
+fn init_into_nested_missing_directory() {
+    let root = scratch_dir("init_into_nested_missing_directory");
+    let lua = root.join("new_pack").join("Lua");
+    let config = serde_json::from_str::<Config>(&config_json("scaffolded", &[])).unwrap();
+    let baba_mod = BabaMod::init(LuaFile::from(""), lua.clone(), config).unwrap();
+    assert_eq!(baba_mod.path(), lua.join("scaffolded"));
+    assert!(lua.join("scaffolded").join("Config.json").exists());
+}
+
+/// Tests whether mods sharing an init file are reported
+#[test]
+fn validate_mods_reports_shared_init() {
+    let root = scratch_dir("validate_mods_reports_shared_init");
+    let pack_path = fixture_pack(&root, "pack", "[general]\nname=Pack\nmods=1\n");
+    for folder in ["first", "second"] {
+        let path = pack_path.join("Lua").join(folder);
+        fs::create_dir_all(&path).unwrap();
+        let config = config_with(
+            folder,
+            &[],
+            serde_json::json!({ "init": "shared_init.lua" }),
+        );
+        fs::write(path.join("Config.json"), config.to_string()).unwrap();
+    }
+    fs::write(pack_path.join("Lua").join("shared_init.lua"), "").unwrap();
+
+    let pack = LevelpackRepr::new(pack_path.clone()).unwrap();
+    let findings = pack.validate_mods();
+    assert_eq!(findings.len(), 1, "{:?}", findings);
+    assert!(matches!(
+        &findings[0],
+        ModdingError::DuplicateInit(path, mods)
+            if *path == pack_path.join("Lua").join("shared_init.lua")
+                && *mods == vec!["first".to_owned(), "second".to_owned()]
+    ));
+}
+
+/// Tests whether a native function can be looked up by name
+#[test]
+fn native_function_lookup() {
+    let root = scratch_dir("native_function_lookup");
+    fs::create_dir_all(root.join("Data")).unwrap();
+    fs::write(
+        root.join("Data").join("movement.lua"),
+        "function movecommand(ox, oy, dir_, playerid_)\n\tlocal take = 1\nend\n",
+    )
+    .unwrap();
+    let files = BabaFiles::from_raw(root);
+    let function = files.native_function("movecommand").unwrap();
+    assert_eq!(function.definition().args(), "ox, oy, dir_, playerid_");
+    assert!(files.native_function("not_a_function").is_none());
+}
+
+/// Tests whether a native function defined in several files is only kept once, from the first file
+#[test]
+fn first_native_definition_wins() {
+    let root = scratch_dir("first_native_definition_wins");
+    fs::create_dir_all(root.join("Data")).unwrap();
+    for (name, value) in [("blocks", "first"), ("changes", "second")] {
+        fs::write(
+            root.join("Data").join(format!("{}.lua", name)),
+            format!(
+                "function init()\n\tlocal a = \"{}\"\nend\nfunction helper()\nend\n",
+                value
+            ),
+        )
+        .unwrap();
+    }
+    let files = BabaFiles::from_raw(root);
+    let functions = files.native_baba_lua_functions();
+    // only baba's own functions are kept
+    assert!(functions
+        .iter()
+        .all(|func| func.definition().is_baba_native()));
+    let inits: Vec<LuaFunction> = functions
+        .into_iter()
+        .filter(|func| func.definition().name() == "init")
+        .collect();
+    assert_eq!(inits.len(), 1);
+    assert!(inits[0].code().contains("first"));
+    assert!(files
+        .native_function("init")
+        .unwrap()
+        .code()
+        .contains("first"));
+}
+
+/// Tests whether the native files missing from an installation are reported by name
+#[test]
+fn verify_native_files_reports_missing() {
+    let root = scratch_dir("verify_native_files_reports_missing");
+    let data = root.join("Data");
+    fs::create_dir_all(&data).unwrap();
+    let files = BabaFiles::from_raw(root);
+    for name in BABA_LUA_FILE_NAMES {
+        fs::write(data.join(format!("{}.lua", name)), "").unwrap();
+    }
+    fs::remove_file(data.join("movement.lua")).unwrap();
+    assert_eq!(
+        files.verify_native_files(),
+        Err(vec!["movement".to_owned()])
+    );
+}
+
+/// Tests whether a folder without baba's data isn't accepted as an installation
+#[test]
+fn checked_install_rejects_bogus_directory() {
+    let root = scratch_dir("checked_install_rejects_bogus_directory");
+    let result = BabaFiles::from_raw_checked(root.clone());
+    assert!(matches!(
+        result,
+        Err(BabaError::Install(InstallError::MissingDataFolder(_)))
+    ));
+    fs::create_dir_all(root.join("Data")).unwrap();
+    fs::write(root.join("Data").join("blocks.lua"), "").unwrap();
+    let result = BabaFiles::from_raw_checked(root);
+    assert!(matches!(
+        result,
+        Err(BabaError::Install(InstallError::TooFewNativeFiles(_, 1)))
+    ));
+}
+
+/// Tests whether a folder laid out like an installation is accepted
+#[test]
+fn checked_install_accepts_baba_layout() {
+    let root = scratch_dir("checked_install_accepts_baba_layout");
+    fs::create_dir_all(root.join("Data").join("Worlds")).unwrap();
+    for name in BABA_LUA_FILE_NAMES {
+        fs::write(root.join("Data").join(format!("{}.lua", name)), "").unwrap();
+    }
+    assert!(BabaFiles::from_raw_checked(root).is_ok());
+}
+
+/// Tests whether fields the config doesn't know about are kept when saving it
+#[test]
+fn config_keeps_unknown_fields() {
+    let json = config_with(
+        "custom",
+        &[],
+        serde_json::json!({ "my_custom_field": { "nested": [1, 2, 3] } }),
+    );
+    let config = Config::from_json(json).unwrap();
+    let reparsed: Config = serde_json::from_str(&config.as_file()).unwrap();
+    assert_eq!(
+        reparsed.extra().get("my_custom_field"),
+        Some(&serde_json::json!({ "nested": [1, 2, 3] }))
+    );
+    assert_eq!(reparsed.modid(), "custom");
+}
+
+/// Tests whether configs are saved pretty printed
+#[test]
+fn config_is_pretty_printed() {
+    let config: Config = serde_json::from_str(&config_json("pretty", &["rock"])).unwrap();
+    let file = config.as_file();
+    assert!(file.contains('\n'));
+    assert!(file.contains("\n  \"modid\": \"pretty\""));
+    let reparsed: Config = serde_json::from_str(&file).unwrap();
+    assert_eq!(reparsed, config);
+}
+
+/// Tests whether icons and banners at local paths are used in place, without being cached
+#[test]
+fn fetch_icon_from_local_path() {
+    let root = scratch_dir("fetch_icon_from_local_path");
+    let icon = root.join("icon.png");
+    fs::write(&icon, "").unwrap();
+    let json = config_with(
+        "icons",
+        &[],
+        serde_json::json!({
+            "icon_url": format!("file://{}", icon.display()),
+            "banner_url": icon.display().to_string(),
+        }),
+    );
+    let config = Config::from_json(json).unwrap();
+    let cache = root.join("cache");
+    assert_eq!(config.fetch_icon(&cache).unwrap(), Some(icon.clone()));
+    assert_eq!(config.fetch_banner(&cache).unwrap(), Some(icon));
+    // nothing needed downloading, so nothing was cached
+    assert!(!cache.exists());
+
+    let no_icon: Config = serde_json::from_str(&config_json("no_icon", &[])).unwrap();
+    assert_eq!(no_icon.fetch_icon(&cache).unwrap(), None);
+}
+
+/// Tests whether cached downloads are keyed on their contents, rather than their url
+#[test]
+fn cache_is_keyed_on_contents() {
+    let cache = scratch_dir("cache_is_keyed_on_contents");
+    let first = store_in_cache(&cache, "https://example.com/a.png", b"icon").unwrap();
+    let shared = store_in_cache(&cache, "https://example.org/b.png?size=2", b"icon").unwrap();
+    assert_eq!(first, shared);
+    assert_eq!(first.extension().unwrap(), "png");
+
+    // a changed file replaces what was cached, but the old one is kept while another url uses it
+    let changed = store_in_cache(&cache, "https://example.com/a.png", b"new icon").unwrap();
+    assert_ne!(changed, first);
+    assert_eq!(fs::read(&changed).unwrap(), b"new icon");
+    assert!(first.exists());
+    store_in_cache(&cache, "https://example.org/b.png?size=2", b"new icon").unwrap();
+    assert!(!first.exists());
+}
+
+/// Tests whether a merge producing unbalanced `end`s is rejected, naming the offending line
+#[test]
+fn merge_rejects_unbalanced_ends() {
+    let left = LuaFile::from("function a()\n\tif x then\n\tend\nend\nend");
+    let right = LuaFile::from("function c()\nend");
+    let result = merge_files(left, right, &[]);
+    assert!(
+        matches!(
+            result,
+            Err(BabaError::Modding(ModdingError::MergeProducedInvalidLua(_, ref region))) if region == "end"
+        ),
+        "{:?}",
+        result
+    );
+}
+
+/// Tests whether `end`s inside of comments and strings are ignored when checking the structure
+#[test]
+fn structure_ignores_comments_and_strings() {
+    let code = "function a()\n\t-- end\n\tprint(\"end)\")\n\t--[[ end\n\tend ]]\nend";
+    assert!(validate_structure(code).is_ok());
+    assert_eq!(
+        validate_structure("function a()\n\tdo\nend")
+            .unwrap_err()
+            .line,
+        1
+    );
+}
+
+/// Tests whether the diff of a merge shows the merged native function
+#[test]
+fn merge_diff_shows_merged_native_function() {
+    let original: LuaFunction = "function init()\n\ta()\nend".parse().unwrap();
+    let left = LuaFile::from("function init()\n\ta()\n\tb()\nend");
+    let right = LuaFile::from("function init()\n\ta()\n\tc()\nend");
+    let diff = merge_files_diff(left, right, &[original]).unwrap();
+    assert!(!diff.is_empty());
+    assert!(diff.starts_with("@@"));
+}
+
+/// Tests whether functions renamed while merging don't clash with existing functions
+#[test]
+fn renamed_functions_avoid_existing_names() {
+    let left = LuaFile::from("function foo()\nend\nfunction foo_left()\n\tfoo()\nend");
+    let right = LuaFile::from("function foo()\nend");
+    let merged = merge_files(left, right, &[]).unwrap().code();
+    assert!(merged.contains("function foo_left2()"));
+    assert!(merged.contains("function foo_left()\n\tfoo_left2()"));
+    assert!(merged.contains("function foo_right()"));
+    assert!(!merged.contains("function foo()"));
+}
+
+/// Tests whether the same function renamed to two different names can't be merged
+#[test]
+fn conflicting_renames_are_rejected() {
+    let left = LuaFile::from("local oldinit = init\nfunction init() oldinit() end");
+    let right = LuaFile::from("local baseinit = init\nfunction init() baseinit() end");
+    assert!(matches!(
+        left.merge_renamed_functions(&right),
+        Err(ModdingError::RenameError { ref function, ref names })
+            if function == "init" && *names == vec!["oldinit".to_owned(), "baseinit".to_owned()]
+    ));
+    let result = merge_files(left, right, &[]);
+    assert!(matches!(
+        result,
+        Err(BabaError::Modding(ModdingError::RenameError { .. }))
+    ));
+}
+
+/// Tests whether a rename only the right file declares is used to glue its injected function
+/// onto the left file's override.
+#[test]
+fn injection_glue_uses_rename_from_right_file() {
+    let left = LuaFile::from("function init()\n\tleft()\nend");
+    let right =
+        LuaFile::from("local baseinit = init\nfunction init()\n\tbaseinit()\n\tright()\nend");
+    let merged = merge_files(left, right, &[]).unwrap();
+    let code = merged.code();
+    let overridden = code.find("function init()\n\tleft()").unwrap();
+    let glue = code.find("local baseinit = init").unwrap();
+    let injected = code.find("\tright()").unwrap();
+    assert!(overridden < glue && glue < injected, "{}", code);
+    assert_eq!(
+        merged.renamed_functions().get("init"),
+        Some(&"baseinit".to_owned())
+    );
+}
+
+/// Tests whether sprites declared in the config, but missing from the pack, are reported
+#[test]
+fn validate_config_reports_missing_sprite() {
+    let root = scratch_dir("validate_config_reports_missing_sprite");
+    let pack_path = fixture_pack(&root, "pack", "[general]\nname=Pack\nmods=1\n");
+    let sprites = pack_path.join("Sprites");
+    fs::create_dir_all(&sprites).unwrap();
+    fs::write(sprites.join("rock_0_1.png"), "").unwrap();
+    let mod_path = pack_path.join("Lua").join("linted");
+    fs::create_dir_all(&mod_path).unwrap();
+    fs::write(
+        mod_path.join("Config.json"),
+        config_json("linted", &["rock", "ghost"]),
+    )
+    .unwrap();
+
+    let issues = BabaMod::new(mod_path).validate_config();
+    assert_eq!(issues, vec![ConfigIssue::MissingSprite("ghost".to_owned())]);
+}
+
+/// Tests whether the files listed in the config can be glob patterns
+#[test]
+fn config_files_expand_globs() {
+    let root = scratch_dir("config_files_expand_globs");
+    let mod_path = root.join("globbed");
+    fs::create_dir_all(&mod_path).unwrap();
+    for file in ["first.lua", "second.lua", "notes.txt"] {
+        fs::write(mod_path.join(file), "").unwrap();
+    }
+    let config = config_with(
+        "globbed",
+        &[],
+        serde_json::json!({ "files": ["*.lua", "sounds/*.ogg"] }),
+    );
+    fs::write(mod_path.join("Config.json"), config.to_string()).unwrap();
+
+    let baba_mod = BabaMod::new(mod_path.clone());
+    let files = baba_mod.all_relevant_files().unwrap();
+    assert!(files.contains(&mod_path.join("first.lua")));
+    assert!(files.contains(&mod_path.join("second.lua")));
+    assert!(!files.contains(&mod_path.join("notes.txt")));
+    assert_eq!(
+        baba_mod.validate_config(),
+        vec![ConfigIssue::PatternMatchedNothing(
+            "sounds/*.ogg".to_owned()
+        )]
+    );
+}
+
+/// Tests whether the size of a mod counts files that are both listed and in its folder once
+#[test]
+fn total_size_counts_every_file_once() {
+    let root = scratch_dir("total_size_counts_every_file_once");
+    let pack_path = fixture_pack(&root, "pack", "[general]\nname=Pack\nmods=1\n");
+    let sprites = pack_path.join("Sprites");
+    fs::create_dir_all(&sprites).unwrap();
+    fs::write(sprites.join("rock_0_1.png"), [0u8; 7]).unwrap();
+    fs::write(sprites.join("rock_0_2.png"), [0u8; 11]).unwrap();
+    fs::write(sprites.join("unrelated_0_1.png"), [0u8; 100]).unwrap();
+    let mod_path = pack_path.join("Lua").join("sized");
+    fs::create_dir_all(&mod_path).unwrap();
+    let config = config_with(
+        "sized",
+        &["rock"],
+        // listed, but also picked up as part of the mod's folder
+        serde_json::json!({ "files": ["code.lua", "../../Sprites/rock_0_1.png"] }),
+    )
+    .to_string();
+    fs::write(mod_path.join("Config.json"), &config).unwrap();
+    fs::write(mod_path.join("code.lua"), "-- 13 bytes!\n").unwrap();
+
+    let size = BabaMod::new(mod_path).total_size().unwrap();
+    assert_eq!(size, config.len() as u64 + 13 + 7 + 11);
+}
+
+/// Tests whether or not `files_in` lists the files of a levelpack folder
+#[test]
+fn files_in_lists_music() {
+    let root = scratch_dir("files_in_lists_music");
+    let path = fixture_pack(&root, "pack", "[general]\nname=Pack\n");
+    let music = path.join("Music");
+    fs::create_dir_all(&music).unwrap();
+    fs::write(music.join("theme.ogg"), "").unwrap();
+    fs::write(music.join("boss.ogg"), "").unwrap();
+    let pack = LevelpackRepr::new(path).unwrap();
+
+    assert_eq!(
+        pack.files_in(LevelpackFile::Music).unwrap(),
+        vec![music.join("boss.ogg"), music.join("theme.ogg")]
+    );
+    assert!(pack.files_in(LevelpackFile::Sprites).unwrap().is_empty());
+}
+
+/// Tests whether every levelpack folder name parses back into the same folder, ignoring case
+#[test]
+fn levelpack_file_round_trips() {
+    for file in LevelpackFile::ALL {
+        let name: String = file.into();
+        assert_eq!(name.parse::<LevelpackFile>().unwrap(), file);
+        assert_eq!(name.to_uppercase().parse::<LevelpackFile>().unwrap(), file);
+    }
+    assert_eq!(
+        "music".parse::<LevelpackFile>().unwrap(),
+        LevelpackFile::Music
+    );
+}
+
+/// Tests whether levels are read from their `.ld` files
+#[test]
+fn levels_are_read_from_ld_files() {
+    let root = scratch_dir("levels_are_read_from_ld_files");
+    let path = fixture_pack(&root, "pack", "[general]\nname=Pack\n");
+    fs::write(
+        path.join("0level.ld"),
+        "[general]\nname=Start\nauthor=Someone\n",
+    )
+    .unwrap();
+    fs::write(path.join("0level.l"), "").unwrap();
+    fs::write(path.join("1level.ld"), "[general]\nname=Unfinished\n").unwrap();
+    let pack = LevelpackRepr::new(path).unwrap();
+
+    let levels = pack.levels().unwrap();
+    assert_eq!(levels.len(), 2);
+    assert_eq!(levels[0].id(), "0level");
+    assert_eq!(levels[0].name(), "Start");
+    assert_eq!(levels[0].author(), "Someone");
+    assert!(levels[0].has_level_file());
+    assert_eq!(levels[1].name(), "Unfinished");
+    assert!(!levels[1].has_level_file());
+    assert_ne!(levels[0], LevelEntry::default());
+}
+
+/// Tests whether or not `fetch_all_fields` skips values that can't be parsed
+#[test]
+fn fetch_all_fields_skips_malformed_values() {
+    let data = "[levels]\nlevel=1\nname=abc\nlevel=two\nlevel=3\n";
+    let levels: Vec<usize> = fetch_all_fields("level", data);
+    assert_eq!(levels, vec![1, 3]);
+}
+
+/// Tests whether two mods can be merged from the command line
+#[test]
+fn command_line_merge() {
+    let args = ["--merge", "a", "b", "--out", "merged"].map(str::to_owned);
+    assert_eq!(
+        Command::from_args(args).unwrap(),
+        Some(Command::Merge {
+            left: "a".into(),
+            right: "b".into(),
+            out: "merged".into(),
+            baba: None,
+        })
+    );
+    assert_eq!(Command::from_args(Vec::new()).unwrap(), None);
+    assert!(matches!(
+        Command::from_args(["--merge", "a", "b"].map(str::to_owned)),
+        Err(CommandError::MissingValue(ref flag, 1)) if flag == "--out"
+    ));
+
+    let root = scratch_dir("command_line_merge");
+    fs::create_dir_all(root.join("baba").join("Data")).unwrap();
+    let files = BabaFiles::from_raw(root.join("baba"));
+    for (id, function) in [("first", "one"), ("second", "two")] {
+        fixture_mod(
+            &root.join("mods"),
+            id,
+            &format!("function {}()\nend\n", function),
+        );
+    }
+    let out = root.join("out");
+    let merged = merge_paths(
+        &root.join("mods").join("first"),
+        &root.join("mods").join("second"),
+        &out,
+        &files,
+    )
+    .unwrap();
+    assert_eq!(merged.path(), out.join("first_x_second"));
+    let code = fs::read_to_string(out.join("first_x_second").join("merged.lua")).unwrap();
+    assert!(code.contains("function one()"));
+    assert!(code.contains("function two()"));
+
+    let missing = merge_paths(
+        &root.join("nowhere"),
+        &root.join("mods").join("first"),
+        &out,
+        &files,
+    );
+    assert!(matches!(
+        missing,
+        Err(BabaError::Command(CommandError::ModNotFound(_)))
+    ));
+}
+
+/// Tests whether a folder with a dot in its name isn't mistaken for a singleton mod
+#[test]
+fn dotted_directories_are_not_singletons() {
+    let root = scratch_dir("dotted_directories_are_not_singletons");
+    let folder = root.join("foo.bar");
+    fs::create_dir_all(&folder).unwrap();
+    fs::write(folder.join("Config.json"), config_json("foo.bar", &[])).unwrap();
+    fs::write(root.join("single.lua"), "function a()\nend\n").unwrap();
+
+    let folder_mod = BabaMod::new(folder);
+    assert_eq!(folder_mod.kind(), ModKind::Directory);
+    assert!(!folder_mod.is_singleton());
+    let file_mod = BabaMod::new(root.join("single.lua"));
+    assert_eq!(file_mod.kind(), ModKind::File);
+    assert!(file_mod.is_singleton());
+}
+
+/// Tests whether reading files in parallel gives the same files as reading them one by one
+#[test]
+fn parallel_and_sequential_reads_agree() {
+    let root = scratch_dir("parallel_and_sequential_reads_agree");
+    let mut paths = Vec::new();
+    for i in 0..8 {
+        let path = root.join(format!("file{}.lua", i));
+        fs::write(
+            &path,
+            format!(
+                "function init()\n\tlocal a = {}\nend\nfunction helper{}()\nend\n",
+                i, i
+            ),
+        )
+        .unwrap();
+        paths.push(path);
+    }
+    paths.insert(3, root.join("missing.lua"));
+
+    let parallel = LuaFile::read_all(&paths);
+    let sequential = LuaFile::read_all_sequentially(&paths);
+    assert_eq!(parallel.len(), 8);
+    assert_eq!(
+        parallel.iter().map(LuaFile::code).collect::<Vec<_>>(),
+        sequential.iter().map(LuaFile::code).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        parallel
+            .iter()
+            .map(LuaFile::definitions)
+            .collect::<Vec<_>>(),
+        sequential
+            .iter()
+            .map(LuaFile::definitions)
+            .collect::<Vec<_>>()
+    );
+}
+
+/// Tests whether a config that can't be read names its path in the error
+#[test]
+fn missing_config_reports_its_path() {
+    let root = scratch_dir("missing_config_reports_its_path");
+    let path = root.join("Config.json");
+    let error = Config::load(&path).unwrap_err();
+    assert!(matches!(error, BabaError::IoAt { path: ref at, .. } if *at == path));
+    assert!(error.to_string().contains(&format!("{:?}", path)));
+}
+
+/// Tests whether writing into a folder creates it if needed
+#[test]
+fn write_into_creates_missing_folders() {
+    let root = scratch_dir("write_into_creates_missing_folders");
+    let folder = root.join("not").join("yet");
+    "contents"
+        .to_owned()
+        .write_into_using(&folder, "file.txt")
+        .unwrap();
+    assert_eq!(
+        fs::read_to_string(folder.join("file.txt")).unwrap(),
+        "contents"
+    );
+}
+
+/// Tests whether a write that fails partway through leaves the original file as it was
+#[test]
+fn failed_writes_keep_the_original() {
+    let root = scratch_dir("failed_writes_keep_the_original");
+    let path = root.join("Config.json");
+    fs::write(&path, "original").unwrap();
+    // a folder in the way of the temporary file makes the write fail partway through
+    // (a read-only file wouldn't, since the new file is renamed over it)
+    fs::create_dir_all(temporary_path(&path)).unwrap();
+
+    assert!("replacement"
+        .to_owned()
+        .write_into_using(&root, "Config.json")
+        .is_err());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "original");
+
+    fs::remove_dir(temporary_path(&path)).unwrap();
+    "replacement"
+        .to_owned()
+        .write_into_using(&root, "Config.json")
+        .unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "replacement");
+    assert!(!temporary_path(&path).exists());
+}
+
+/// Tests whether a levelpack written elsewhere loads back the same
+#[test]
+fn levelpack_write_into_round_trips() {
+    let root = scratch_dir("levelpack_write_into_round_trips");
+    let original = LevelpackRepr::new(fixture_pack(
+        &root,
+        "original",
+        "[general]\nname=Pack\nauthor=Someone\nprize_max=12\nclear_max=3\nbonus_max=1\nmods=1\n[levels]\n0level=1\n",
+    ))
+    .unwrap();
+    let copy_path = root.join("copy");
+    original.write_into(&copy_path).unwrap();
+    let copy = LevelpackRepr::new(copy_path.clone()).unwrap();
+
+    assert_eq!(copy.name(), original.name());
+    assert_eq!(copy.author(), original.author());
+    assert_eq!(copy.prize_max(), original.prize_max());
+    assert_eq!(copy.clear_max(), original.clear_max());
+    assert_eq!(copy.bonus_max(), original.bonus_max());
+    assert_eq!(copy.mods_enabled(), original.mods_enabled());
+    let written = fs::read_to_string(copy_path.join("world_data.txt")).unwrap();
+    assert!(written.contains("[levels]\n0level=1"));
+}
+
+/// Tests whether more than two mods can be merged, in order
+#[test]
+fn merge_many_merges_three_mods() {
+    let root = scratch_dir("merge_many_merges_three_mods");
+    let mods: Vec<BabaMod> = [("a", "one"), ("b", "two"), ("c", "three")]
+        .iter()
+        .map(|(id, function)| {
+            BabaMod::new(fixture_mod(
+                &root.join("mods"),
+                id,
+                &format!("function {}()\nend\n", function),
+            ))
+        })
+        .collect();
+    let options = MergeOptions {
+        location: root.join("out"),
+        ..Default::default()
+    };
+    let merged = merge_many(&mods.iter().collect::<Vec<_>>(), &[], options).unwrap();
+    assert_eq!(merged.mod_id(), "a_x_b_x_c");
+    let code = fs::read_to_string(merged.path().join("merged.lua")).unwrap();
+    let positions: Vec<usize> = ["function one()", "function two()", "function three()"]
+        .iter()
+        .map(|function| code.find(function).unwrap())
+        .collect();
+    assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+
+    let clashing: Vec<BabaMod> = [("d", "oldinit"), ("e", "baseinit")]
+        .iter()
+        .map(|(id, rename)| {
+            BabaMod::new(fixture_mod(
+                &root.join("mods"),
+                id,
+                &format!(
+                    "local {} = init\nfunction init() {}() end\n",
+                    rename, rename
+                ),
+            ))
+        })
+        .collect();
+    let options = MergeOptions {
+        location: root.join("out"),
+        ..Default::default()
+    };
+    assert!(matches!(
+        merge_many(&[&mods[0], &clashing[0], &clashing[1]], &[], options),
+        Err(BabaError::Modding(ModdingError::MergeStepFailed(ref merged, ref next, _)))
+            if *merged == vec!["a".to_owned(), "d".to_owned()] && next == "e"
+    ));
+
+    assert!(matches!(
+        merge_many(&[&mods[0]], &[], MergeOptions::default()),
+        Err(BabaError::Modding(ModdingError::NotEnoughMods(1)))
+    ));
+}
+
+/// Tests whether the merge priority decides which mod comes first
+#[test]
+fn merge_priority_decides_which_mod_comes_first() {
+    let root = scratch_dir("merge_priority_decides_which_mod_comes_first");
+    let mods: Vec<BabaMod> = ["a", "b"]
+        .iter()
+        .map(|id| {
+            BabaMod::new(fixture_mod(
+                &root.join("mods"),
+                id,
+                &format!("function helper()\n\treturn \"{}\"\nend\n", id),
+            ))
+        })
+        .collect();
+    let mods: Vec<&BabaMod> = mods.iter().collect();
+    let merged_code = |folder: &str, priority: &[&str]| {
+        let options = MergeOptions {
+            location: root.join(folder),
+            priority: priority.iter().map(|id| id.to_string()).collect(),
+            ..Default::default()
+        };
+        let merged = merge_many(&mods, &[], options).unwrap();
+        fs::read_to_string(merged.path().join("merged.lua")).unwrap()
+    };
+
+    let in_order = merged_code("in_order", &[]);
+    assert!(in_order.contains("function helper_left()\n\treturn \"a\""));
+    assert!(in_order.contains("function helper_right()\n\treturn \"b\""));
+    let reordered = merged_code("reordered", &["b"]);
+    assert!(reordered.contains("function helper_left()\n\treturn \"b\""));
+    assert!(reordered.contains("function helper_right()\n\treturn \"a\""));
+}
+
+/// Tests whether only assignments count as renaming a function for injection
+#[test]
+fn injection_needs_an_assignment() {
+    let injected = LuaFile::from("local old = init\nfunction init()\n\told()\nend");
+    assert_eq!(
+        injected.renamed_functions().get("init"),
+        Some(&"old".to_owned())
+    );
+    let global = LuaFile::from("old_init = init -- keep the original around");
+    assert_eq!(
+        global.renamed_functions().get("init"),
+        Some(&"old_init".to_owned())
+    );
+
+    let called = LuaFile::from("function setup()\n\tfoo(init)\n\tprint(init)\nend");
+    assert!(called.renamed_functions().is_empty());
+    let compared = LuaFile::from("if old == init then\nend\n-- local fake = init");
+    assert!(compared.renamed_functions().is_empty());
+}
+
+/// Tests whether the global flag survives the config being saved
+#[test]
+fn global_flag_round_trips() {
+    let json = config_with("everywhere", &[], serde_json::json!({ "global": true }));
+    let mut config = Config::from_json(json).unwrap();
+    assert!(config.is_global());
+    let reparsed: Config = serde_json::from_str(&config.as_file()).unwrap();
+    assert!(reparsed.is_global());
+    config.set_global(false);
+    let reparsed: Config = serde_json::from_str(&config.as_file()).unwrap();
+    assert!(!reparsed.is_global());
+}
+
+/// Tests whether global mods are installed into the game's own `Lua` folder
+#[test]
+fn global_mods_install_game_wide() {
+    let root = scratch_dir("global_mods_install_game_wide");
+    let files = BabaFiles::from_raw(root.join("baba"));
+    let pack_path = fixture_pack(&root, "pack", "[general]\nname=Pack\n");
+    let pack = LevelpackRepr::new(pack_path.clone()).unwrap();
+    let mod_path = root.join("everywhere");
+    fs::create_dir_all(&mod_path).unwrap();
+    let json = config_with("everywhere", &[], serde_json::json!({ "global": true }));
+    fs::write(mod_path.join("Config.json"), json.to_string()).unwrap();
+
+    let baba_mod = BabaMod::new(mod_path);
+    assert!(baba_mod.is_global());
+    pack.install_mod(&baba_mod, &files).unwrap();
+    assert!(files
+        .global_mods_dir()
+        .join("everywhere")
+        .join("Config.json")
+        .is_file());
+    assert!(!pack_path.join("Lua").exists());
+}
+
+/// Tests whether placeholder and malformed links aren't counted as valid
+#[test]
+fn valid_links_skip_placeholders() {
+    let json = config_with(
+        "linked",
+        &[],
+        serde_json::json!({ "links": [
+            "https://example.com/mods/linked",
+            "[Intentionally left without links]",
+            "http://",
+            "not a link"
+        ] }),
+    );
+    let config = Config::from_json(json).unwrap();
+    assert_eq!(config.links().len(), 4);
+    assert_eq!(
+        config.valid_links(),
+        vec!["https://example.com/mods/linked".to_owned()]
+    );
+}
+
+/// Tests whether a `config.json` is found when `Config.json` is asked for
+#[test]
+fn lowercase_config_is_found() {
+    let root = scratch_dir("lowercase_config");
+    let mod_path = root.join("lowercase");
+    fs::create_dir_all(&mod_path).unwrap();
+    fs::write(mod_path.join("config.json"), config_json("lowercase", &[])).unwrap();
+
+    let baba_mod = BabaMod::new(mod_path.clone());
+    assert!(baba_mod.has_config());
+    let config = Config::load(&mod_path.join("Config.json")).unwrap();
+    assert_eq!(config.modid(), "lowercase");
+    assert!(Config::load(&mod_path.join("settings.json")).is_err());
+}
+
+/// Tests whether renaming a function only renames whole identifiers in code
+#[test]
+fn rename_only_touches_whole_identifiers() {
+    let function: LuaFunction =
+        "function foo(n)\n\tif n > 0 then\n\t\tfoo(n - 1)\n\tend\n\tfoobar(\"foo\") -- foo\nend"
+            .parse()
+            .unwrap();
+    let renamed = function.rename("bar");
+    assert_eq!(renamed.definition().name(), "bar");
+    assert_eq!(
+        renamed.code(),
+        "function bar(n)\n\tif n > 0 then\n\t\tbar(n - 1)\n\tend\n\tfoobar(\"foo\") -- foo\nend"
+    );
+    // the original is left as it was
+    assert_eq!(function.definition().name(), "foo");
+}
+
+/// Tests whether the functions a function calls are found, ignoring strings, comments, and methods
+#[test]
+fn calls_lists_called_functions() {
+    let function: LuaFunction = "function tidy(list)\n\tlocal first = first_of(list)\n\tif (first ~= nil) then\n\t\ttable.insert(list, clean( first ))\n\tend\n\tlist:sort()\n\tprint(\"skipped(\") -- ignored()\nend"
+        .parse()
+        .unwrap();
+    let expected = ["first_of", "table.insert", "clean", "print"]
+        .map(ToOwned::to_owned)
+        .into_iter()
+        .collect::<HashSet<String>>();
+    assert_eq!(function.calls(), expected);
+}
+
+/// Tests whether the dependency graph of a pack follows calls between its mods
+#[test]
+fn dependency_graph_follows_calls() {
+    let root = scratch_dir("dependency_graph_follows_calls");
+    let pack_path = fixture_pack(&root, "pack", "[general]\nname=Pack\nmods=1\n");
+    let lua = pack_path.join("Lua");
+    for (id, code) in [
+        ("library", "function shared_helper(x)\n\treturn x\nend\n"),
+        (
+            "user",
+            "function use_it()\n\treturn shared_helper(1)\nend\n",
+        ),
+    ] {
+        fixture_mod(&lua, id, code);
+    }
+    // a folder that isn't a mod at all is skipped over
+    fs::create_dir_all(lua.join("broken")).unwrap();
+
+    let pack = LevelpackRepr::new(pack_path).unwrap();
+    let graph = pack.dependency_graph();
+    assert_eq!(graph["user"], HashSet::from(["library".to_owned()]));
+    assert!(graph["library"].is_empty());
+    assert!(graph["broken"].is_empty());
+}
+
+/// Tests whether unused declarations and undeclared sprites are both found
+#[test]
+fn sprite_declarations_are_checked_both_ways() {
+    let root = scratch_dir("sprite_declarations_are_checked_both_ways");
+    let pack_path = fixture_pack(&root, "pack", "[general]\nname=Pack\nmods=1\n");
+    let sprites = pack_path.join("Sprites");
+    fs::create_dir_all(&sprites).unwrap();
+    for sprite in ["rock_0_1.png", "rock_0_2.png", "leftover_0_1.png"] {
+        fs::write(sprites.join(sprite), "").unwrap();
+    }
+    let mod_path = pack_path.join("Lua").join("linted");
+    fs::create_dir_all(&mod_path).unwrap();
+    fs::write(
+        mod_path.join("Config.json"),
+        config_json("linted", &["rock", "ghost"]),
+    )
+    .unwrap();
+
+    let baba_mod = BabaMod::new(mod_path);
+    assert_eq!(
+        baba_mod.unused_sprite_declarations().unwrap(),
+        vec!["ghost".to_owned()]
+    );
+    assert_eq!(
+        baba_mod.undeclared_sprites().unwrap(),
+        vec!["leftover_0_1.png".to_owned()]
+    );
+}
+
+/// Tests whether sprites only match the exact object they're named after
+#[test]
+fn sprite_names_match_exactly() {
+    assert_eq!(sprite_base_name("baba_0_1.png"), "baba");
+    assert_eq!(sprite_base_name("text_baba_0_1.png"), "text_baba");
+    assert!(sprite_matches("baba", "baba_24_3.png"));
+    assert!(!sprite_matches("baba", "megababa_0_1.png"));
+    assert!(!sprite_matches("baba", "babayou_0_1.png"));
+    assert!(!sprite_matches("baba", "text_baba_0_1.png"));
+
+    let root = scratch_dir("sprite_names_match_exactly");
+    let pack_path = fixture_pack(&root, "pack", "[general]\nname=Pack\nmods=1\n");
+    let sprites = pack_path.join("Sprites");
+    fs::create_dir_all(&sprites).unwrap();
+    for sprite in ["baba_0_1.png", "megababa_0_1.png"] {
+        fs::write(sprites.join(sprite), "").unwrap();
+    }
+    let mod_path = pack_path.join("Lua").join("babas");
+    fs::create_dir_all(&mod_path).unwrap();
+    fs::write(
+        mod_path.join("Config.json"),
+        config_json("babas", &["baba"]),
+    )
+    .unwrap();
+    let files = BabaMod::new(mod_path).sprite_files().unwrap();
+    assert_eq!(files, vec![sprites.join("baba_0_1.png")]);
+}
+
+/// Tests whether an exported mod imports back with its code and sprites
+#[test]
+fn exported_archive_round_trips() {
+    let root = scratch_dir("exported_archive_round_trips");
+    let pack_path = fixture_pack(&root, "pack", "[general]\nname=Pack\nmods=1\n");
+    let sprites = pack_path.join("Sprites");
+    fs::create_dir_all(&sprites).unwrap();
+    fs::write(sprites.join("shared_0_1.png"), "sprite").unwrap();
+    fs::write(sprites.join("unrelated_0_1.png"), "sprite").unwrap();
+    let mod_path = fixture_mod(
+        &pack_path.join("Lua"),
+        "exported",
+        "function exported()\nend\n",
+    );
+    fs::write(
+        mod_path.join("Config.json"),
+        config_json("exported_mod", &["shared"]),
+    )
+    .unwrap();
+
+    let archive = root.join("out").join("exported.zip");
+    BabaMod::new(mod_path).export_as_zip(&archive).unwrap();
+    let imported = BabaMod::from_archive(&archive).unwrap();
+    assert_eq!(imported.mod_id(), "exported_mod");
+    assert!(imported.path().join("exported.lua").is_file());
+    let sprite_names: Vec<String> = imported
+        .sprite_files()
+        .unwrap()
+        .iter()
+        .map(|sprite| sprite.file_name().unwrap().to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(sprite_names, vec!["shared_0_1.png".to_owned()]);
+}
+
+/// Tests whether editing one file of a mod only changes that file's manifest entry
+#[test]
+fn editing_a_file_changes_one_manifest_entry() {
+    let root = scratch_dir("editing_a_file_changes_one_manifest_entry");
+    let mod_path = fixture_mod(&root, "checked", "function checked()\nend\n");
+    fs::write(mod_path.join("notes.txt"), "some notes").unwrap();
+    let baba_mod = BabaMod::new(mod_path.clone());
+    let before = baba_mod.manifest().unwrap();
+    assert_eq!(before.len(), 3);
+    // the manifest survives being saved as json
+    let json = serde_json::to_string(&before).unwrap();
+    assert_eq!(serde_json::from_str::<Manifest>(&json).unwrap(), before);
+
+    fs::write(
+        mod_path.join("checked.lua"),
+        "function checked()\n\tedited()\nend\n",
+    )
+    .unwrap();
+    let after = baba_mod.manifest().unwrap();
+    let changed: Vec<&PathBuf> = before
+        .keys()
+        .filter(|path| before[*path] != after[*path])
+        .collect();
+    assert_eq!(changed, vec![&PathBuf::from("checked/checked.lua")]);
+
+    let diff = baba_mod.verify_against(&before).unwrap();
+    assert_eq!(diff.changed, vec![PathBuf::from("checked/checked.lua")]);
+    assert!(diff.added.is_empty() && diff.removed.is_empty());
+    assert!(baba_mod.verify_against(&after).unwrap().is_unchanged());
+}
+
+/// Tests whether levelpacks sharing a name are grouped together
 #[test]
-fn load_palette_data() {
-    let value = load_themes();
-    assert!(value.is_ok(), "{:?}", value.err().unwrap())
+fn duplicate_pack_names_are_grouped() {
+    let root = scratch_dir("duplicate_pack_names_are_grouped");
+    let worlds = root.join("Data").join("Worlds");
+    let first = fixture_pack(&worlds, "first", "[general]\nname=Same Name\n");
+    let second = fixture_pack(&worlds, "second", "[general]\nname=Same Name\n");
+    fixture_pack(&worlds, "third", "[general]\nname=Another Name\n");
+
+    let files = BabaFiles::from_raw(root);
+    assert_eq!(
+        files.duplicate_pack_names().unwrap(),
+        vec![("Same Name".to_owned(), vec![first, second])]
+    );
+}
+
+/// Tests whether restoring a backup undoes changes made after it was taken
+#[test]
+fn restoring_a_backup_undoes_changes() {
+    let root = scratch_dir("restoring_a_backup_undoes_changes");
+    let game = root.join("game");
+    let pack_path = fixture_pack(
+        &game.join("Data").join("Worlds"),
+        "pack",
+        "[general]\nname=Pack\nmods=1\n",
+    );
+    fs::create_dir_all(game.join("Lua")).unwrap();
+    fs::write(
+        game.join("Lua").join("global.lua"),
+        "function global()\nend\n",
+    )
+    .unwrap();
+    fixture_mod(&pack_path.join("Lua"), "packed", "function packed()\nend\n");
+    fs::create_dir_all(pack_path.join("Sprites")).unwrap();
+    fs::write(pack_path.join("Sprites").join("rock_0_1.png"), "rock").unwrap();
+
+    let snapshot = || {
+        let mut manifest = Manifest::new();
+        add_to_manifest(&mut manifest, &game.join("Lua"), "Lua").unwrap();
+        add_to_manifest(&mut manifest, &pack_path.join("Lua"), "pack/Lua").unwrap();
+        add_to_manifest(&mut manifest, &pack_path.join("Sprites"), "pack/Sprites").unwrap();
+        manifest
+    };
+    let original = snapshot();
+
+    let files = BabaFiles::from_raw(game.clone());
+    let pack = LevelpackRepr::new(pack_path.clone()).unwrap();
+    let backup = files.backup(&pack, &root.join("backups")).unwrap();
+    assert!(backup.starts_with(root.join("backups")));
+
+    // a merge gone wrong
+    fs::write(game.join("Lua").join("global.lua"), "broken").unwrap();
+    fs::write(pack_path.join("Lua").join("leftover.lua"), "").unwrap();
+    fs::remove_file(pack_path.join("Sprites").join("rock_0_1.png")).unwrap();
+    assert_ne!(snapshot(), original);
+
+    files.restore(&backup).unwrap();
+    assert_eq!(snapshot(), original);
+    assert!(files
+        .restore(&root.join("backups").join("missing"))
+        .is_err());
 }
 
+/// Tests whether binary files aren't parsed as lua, even when named like it
 #[test]
-fn correct_amount_of_palettes() {
-    let value = load_themes().unwrap();
-    assert!(value.len() == 20, "{:?}", value);
+fn binary_files_are_not_parsed_as_lua() {
+    let root = scratch_dir("binary_files_are_not_parsed_as_lua");
+    let mod_path = fixture_mod(&root, "binary", "function real()\nend\n");
+    let png = [
+        0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n', 0, 0, 0, 13,
+    ];
+    fs::write(mod_path.join("sprite.png"), png).unwrap();
+    // a binary file that only pretends to be lua
+    fs::write(mod_path.join("fake.lua"), png).unwrap();
+    let config = config_with(
+        "binary",
+        &[],
+        serde_json::json!({ "files": ["binary.lua", "sprite.png", "fake.lua"] }),
+    );
+    fs::write(mod_path.join("Config.json"), config.to_string()).unwrap();
+
+    assert!(is_probably_text(&mod_path.join("binary.lua")));
+    assert!(!is_probably_text(&mod_path.join("sprite.png")));
+    let baba_mod = BabaMod::new(mod_path.clone());
+    assert_eq!(
+        baba_mod.lua_file_paths(false),
+        vec![mod_path.join("binary.lua")]
+    );
+    let names: Vec<String> = baba_mod
+        .defined_function_definitions()
+        .iter()
+        .map(LuaFuncDef::name)
+        .collect();
+    assert_eq!(names, vec!["real".to_owned()]);
 }
 
+/// Tests whether files with CRLF line endings are still split into functions
 #[test]
-fn load_font_data() {
-    let value = load_fonts();
-    assert!(value.is_ok(), "{:?}", value.err().unwrap())
+fn crlf_files_are_split_into_functions() {
+    let code = "function first()\r\n\tone()\r\nend\r\n\r\nfunction second()\r\n\ttwo()\r\nend\r\n";
+    let functions = code_to_funcs(code);
+    let names: Vec<String> = functions
+        .iter()
+        .map(|function| function.definition().name())
+        .collect();
+    assert_eq!(names, vec!["first".to_owned(), "second".to_owned()]);
+    assert!(functions
+        .iter()
+        .all(|function| !function.code().contains('\r')));
+
+    let file = LuaFile::from(code);
+    assert_eq!(file.functions().len(), 2);
+    assert!(!file.code().contains('\r'));
+}
+
+/// Tests whether splitting code into functions only gives back the `end`s it took away
+#[test]
+fn end_is_only_reattached_where_removed() {
+    let code = "function first()\n\tone()\nend\n\nfunction second() two() end\nprint(\"loaded\")";
+    let functions = code_to_funcs(code);
+    let names: Vec<String> = functions
+        .iter()
+        .map(|function| function.definition().name())
+        .collect();
+    assert_eq!(names, vec!["first".to_owned(), "second".to_owned()]);
+    assert_eq!(functions[0].code(), "function first()\n\tone()\nend");
+    // the last chunk never had an unindented `end`, so it doesn't get one
+    assert_eq!(
+        functions[1].code(),
+        "function second() two() end\nprint(\"loaded\")"
+    );
+}
+
+/// Tests whether merging reports its progress once per mod
+#[test]
+fn merge_progress_is_reported_per_step() {
+    let root = scratch_dir("merge_progress_is_reported_per_step");
+    let mods: Vec<BabaMod> = [("a", "one"), ("b", "two"), ("c", "three")]
+        .iter()
+        .map(|(id, function)| {
+            BabaMod::new(fixture_mod(
+                &root.join("mods"),
+                id,
+                &format!("function {}()\nend\n", function),
+            ))
+        })
+        .collect();
+    let options = MergeOptions {
+        location: root.join("out"),
+        ..Default::default()
+    };
+    let mut steps = Vec::new();
+    merge_many_with_progress(&mods.iter().collect::<Vec<_>>(), &[], options, |progress| {
+        steps.push(progress)
+    })
+    .unwrap();
+    let expected: Vec<MergeProgress> = ["a", "b", "c"]
+        .iter()
+        .enumerate()
+        .map(|(index, id)| MergeProgress {
+            current: index + 1,
+            total: 3,
+            label: id.to_string(),
+        })
+        .collect();
+    assert_eq!(steps, expected);
+    assert_eq!(steps[2].fraction(), 1.0);
+}
+
+/// Tests whether a config can be loaded from either its mod's folder or the file itself
+#[test]
+fn config_loads_from_folder_or_file() {
+    let root = scratch_dir("config_loads_from_folder_or_file");
+    let mod_path = fixture_mod(&root, "loaded", "");
+    let from_folder = Config::load(&mod_path).unwrap();
+    let from_file = Config::load(&mod_path.join("Config.json")).unwrap();
+    assert_eq!(from_folder.modid(), "loaded");
+    assert_eq!(from_folder, from_file);
+    #[allow(deprecated)]
+    let from_new = Config::new(mod_path.join("Config.json")).unwrap();
+    assert_eq!(from_new, from_file);
+    // a folder without a config reports where the config should have been
+    let empty = root.join("empty");
+    fs::create_dir_all(&empty).unwrap();
+    assert!(matches!(
+        Config::load(&empty),
+        Err(BabaError::IoAt { ref path, .. }) if *path == empty.join("Config.json")
+    ));
+}
+
+/// Tests whether the names of baba's functions are only built once
+#[test]
+fn baba_function_names_are_built_once() {
+    let first = baba_function_names();
+    let second = baba_function_names();
+    assert!(std::ptr::eq(first, second));
+    assert!(!first.contains(""));
+    assert!(is_baba_native_name("addaction"));
+    assert!(!is_baba_native_name("my_own_function"));
+}
+
+/// Tests whether renamed functions are found in long files
+#[test]
+fn renames_are_found_in_long_files() {
+    let mut code = String::new();
+    for i in 0..5000 {
+        code.push_str(&format!(
+            "local value_{0} = {0}\nfunction helper_{0}()\n\treturn init\nend\n",
+            i
+        ));
+    }
+    code.push_str("local old_init = init\nbase_addaction = addaction\nlocal not_baba = helper_1\n");
+    let file = LuaFile::from(code);
+    assert_eq!(
+        file.renamed_functions(),
+        HashMap::from([
+            ("init".to_owned(), "old_init".to_owned()),
+            ("addaction".to_owned(), "base_addaction".to_owned()),
+        ])
+    );
+}
+
+/// Tests whether a merged mod lists each author once, without stray quotes or whitespace
+#[test]
+fn merged_authors_are_trimmed_and_deduped() {
+    let root = scratch_dir("merged_authors_are_trimmed_and_deduped");
+    let mods: Vec<BabaMod> = [
+        ("left", serde_json::json!(["Alice", " \"Bob\" "])),
+        ("right", serde_json::json!(["bob, Carol", ""])),
+    ]
+    .into_iter()
+    .map(|(id, authors)| {
+        let code = format!("function {}()\nend\n", id);
+        let overrides = serde_json::json!({ "authors": authors });
+        BabaMod::new(fixture_mod_with(&root.join("mods"), id, &code, overrides))
+    })
+    .collect();
+    assert_eq!(mods[0].authors(), ["Alice", "Bob"]);
+
+    let options = MergeOptions {
+        location: root.join("out"),
+        ..Default::default()
+    };
+    let merged = merge_many(&mods.iter().collect::<Vec<_>>(), &[], options).unwrap();
+    let config = Config::load(merged.path()).unwrap();
+    assert_eq!(config.authors(), ["Alice", "Bob", "Carol"]);
 }
 
+/// Tests whether merged mod ids are the same every time, and only use safe characters
 #[test]
-fn correct_amount_of_fonts() {
-    let value = load_fonts().unwrap();
-    assert!(value.len() == 6, "{:?}", value);
+fn merged_ids_are_stable_and_sanitized() {
+    let root = scratch_dir("merged_ids_are_stable_and_sanitized");
+    let mods: Vec<BabaMod> = ["left", "right"]
+        .iter()
+        .map(|id| {
+            let code = format!("function {}()\nend\n", id);
+            BabaMod::new(fixture_mod(&root.join("mods"), id, &code))
+        })
+        .collect();
+    let merged_id = |out: &str| {
+        let options = MergeOptions {
+            location: root.join(out),
+            ..Default::default()
+        };
+        merge_many(&mods.iter().collect::<Vec<_>>(), &[], options)
+            .unwrap()
+            .mod_id()
+    };
+    let first = merged_id("first");
+    assert_eq!(first, "left_x_right");
+    assert_eq!(first, merged_id("second"));
+
+    let ids = ["my-mod!".to_owned(), "other mod".to_owned()];
+    assert_eq!(merged_mod_id(&ids), "mymod_x_othermod");
+    assert_eq!(merged_mod_id(&ids), merged_mod_id(&ids));
+    let long = vec!["a".repeat(50); 2];
+    assert_eq!(merged_mod_id(&long).len(), MAX_MERGED_ID_LENGTH);
 }
 
+/// Tests whether function definitions are equal when their names are
 #[test]
-fn pixel_index_generic() {
-    assert_eq!(pixel_index(2, 1), 9)
+fn definitions_are_equal_by_name_alone() {
+    let global: LuaFuncDef = "function init()".parse().unwrap();
+    let local: LuaFuncDef = "local function init()".parse().unwrap();
+    assert_ne!(global.is_baba_native(), local.is_baba_native());
+    assert_eq!(global, local);
+    // e.g. a definition saved before baba's names were known
+    let stale: LuaFuncDef =
+        serde_json::from_value(serde_json::json!({"name": "init", "is_baba_native": false}))
+            .unwrap();
+    assert_eq!(global, stale);
+    assert_eq!(HashSet::from([global, local, stale]).len(), 1);
 }
 
+/// Tests whether only mods loaded together can be incompatible
 #[test]
-fn pixel_index_min() {
-    assert_eq!(pixel_index(0, 0), 0)
+fn compatibility_depends_on_scope() {
+    let root = scratch_dir("compatibility_depends_on_scope");
+    let install = |pack: &str, id: &str, global: bool| {
+        let pack_path = fixture_pack(&root, pack, "[general]\nname=Pack\nmods=1\n");
+        BabaMod::new(fixture_mod_with(
+            &pack_path.join("Lua"),
+            id,
+            "function movement()\nend\n",
+            serde_json::json!({ "global": global }),
+        ))
+    };
+    let first = install("first", "local_a", false);
+    let second = install("second", "local_b", false);
+    let same_pack = install("first", "local_c", false);
+    let global = install("second", "global", true);
+    assert_eq!(first.levelpack_dir(), Some(root.join("first")));
+
+    // local mods in different packs are never loaded together
+    assert!(first.is_compatible_with(&second));
+    // but they are in the same pack
+    assert!(!first.is_compatible_with(&same_pack));
+    // and a global mod is loaded alongside everything
+    assert!(!first.is_compatible_with(&global));
+    assert!(!global.is_compatible_with(&first));
 }
 
+/// Tests whether reading functions line by line finds the same functions as reading the whole file
 #[test]
-fn pixel_index_max() {
-    assert_eq!(pixel_index(6, 4), 34)
+fn functions_are_read_line_by_line() {
+    let code = "function first(a)\nend\r\n\nlocal function second()\n\treturn 1\nend\n-- function commented()\nfunction last()";
+    let read = functions_from_reader(std::io::Cursor::new(code));
+    let mut names: Vec<_> = read.iter().map(|function| function.name()).collect();
+    names.sort();
+    assert_eq!(names, ["first", "last", "second"]);
+    assert_eq!(read, functions_from_string(code));
 }
 
+/// Tests whether a pack's mods include both folders and lua files, but not init files or baba's own files
 #[test]
-fn icon_load() {
-    let icon = icon();
-    assert!(icon.is_ok(), "{:?}", icon)
-}
\ No newline at end of file
+fn pack_mods_include_files_and_folders() {
+    let root = scratch_dir("pack_mods_include_files_and_folders");
+    let pack_path = fixture_pack(&root, "pack", "[general]\nname=Pack\nmods=1\n");
+    let lua = pack_path.join("Lua");
+    let folder = fixture_mod_with(
+        &lua,
+        "folder_mod",
+        "function helper()\nend\n",
+        serde_json::json!({ "init": ".\\folder_mod_init.lua" }),
+    );
+    fs::write(lua.join("folder_mod_init.lua"), "-- loads folder_mod\n").unwrap();
+    fs::write(lua.join("weather.lua"), "function rain()\nend\n").unwrap();
+    fs::write(lua.join("rules.lua"), "function codecheck()\nend\n").unwrap();
+    fs::write(lua.join("readme.txt"), "not a mod").unwrap();
+
+    let pack = LevelpackRepr::new(pack_path).unwrap();
+    let mut paths: Vec<PathBuf> = pack
+        .mods()
+        .unwrap()
+        .iter()
+        .map(|baba_mod| baba_mod.path().to_owned())
+        .collect();
+    paths.sort();
+    assert_eq!(paths, [folder, lua.join("weather.lua")]);
+}
+
+/// Tests for the graphical application, which only exists with the `gui` feature
+#[cfg(feature = "gui")]
+mod gui {
+    use crate::{
+        application::{
+            appoptions::AppOptions,
+            appstate::{AppState, ListedPack},
+            find_data_dir, icon, load_fonts, load_fonts_from, load_themes, load_themes_from,
+            matches_filter, mod_from_dropped_file,
+            packsortorder::{compare_packs, PackSortOrder},
+            pixel_index,
+            themedata::ThemeData,
+            DEFAULT_FONT, FONTS_DIR, PALETTES_DIR,
+        },
+        error::{applicationerror::ApplicationError, levelpackerror::LevelpackError},
+    };
+
+    use super::*;
+
+    #[test]
+    fn load_palette_data() {
+        let value = load_themes();
+        assert!(value.is_ok(), "{:?}", value.err().unwrap())
+    }
+
+    #[test]
+    fn correct_amount_of_palettes() {
+        let value = load_themes().unwrap();
+        assert!(value.len() == 20, "{:?}", value);
+    }
+
+    #[test]
+    fn load_font_data() {
+        let value = load_fonts();
+        assert!(value.is_ok(), "{:?}", value.err().unwrap())
+    }
+
+    #[test]
+    fn correct_amount_of_fonts() {
+        let value = load_fonts().unwrap();
+        assert!(value.len() == 6, "{:?}", value);
+    }
+
+    #[test]
+    fn pixel_index_generic() {
+        assert_eq!(pixel_index(2, 1), 9)
+    }
+
+    #[test]
+    fn pixel_index_min() {
+        assert_eq!(pixel_index(0, 0), 0)
+    }
+
+    #[test]
+    fn pixel_index_max() {
+        assert_eq!(pixel_index(6, 4), 34)
+    }
+
+    #[test]
+    fn icon_load() {
+        let icon = icon();
+        assert!(icon.is_ok(), "{:?}", icon)
+    }
+
+    /// Tests whether a levelpack without an icon reports where the icon should have been
+    #[test]
+    fn missing_levelpack_icon() {
+        let root = scratch_dir("missing_levelpack_icon");
+        let path = fixture_pack(&root, "pack", "[general]\nname=Pack\n");
+        let pack = LevelpackRepr::new(path.clone()).unwrap();
+        let icon = pack.icon();
+        assert!(
+            matches!(icon, Err(BabaError::Levelpack(LevelpackError::IconNotFound(ref p))) if *p == path.join("icon.png")),
+            "{:?}",
+            icon.map(|_| ())
+        );
+    }
+
+    /// Tests whether a theme saved as a palette image loads back with the same colors
+    #[test]
+    fn palette_round_trip() {
+        let root = scratch_dir("palette_round_trip");
+        let path = root.join("palette.png");
+        let theme = ThemeData::new([
+            "#010203", "#040506", "#070809", "#0a0b0c", "#0d0e0f", "#101112", "#131415", "#161718",
+            "#191a1b", "#1c1d1e", "#1f2021", "#222324", "#252627",
+        ])
+        .unwrap();
+        theme.save_palette(&path).unwrap();
+        let loaded = ThemeData::from_image_file(&path).unwrap();
+        let colors = |theme: &ThemeData| {
+            [
+                theme.dark,
+                theme.dark_accent,
+                theme.light,
+                theme.light_accent,
+                theme.grey,
+                theme.error,
+                theme.warning,
+                theme.accept,
+                theme.link,
+                theme.link_visited,
+                theme.spore,
+                theme.blossom,
+                theme.bonus,
+            ]
+            .map(|color| color.color())
+        };
+        assert_eq!(colors(&theme), colors(&loaded));
+    }
+
+    /// Tests whether or not the bundled fonts are loaded when the fonts folder doesn't exist
+    #[test]
+    fn bundled_fonts_without_directory() {
+        let root = scratch_dir("bundled_fonts_without_directory");
+        let fonts = load_fonts_from(&root.join("does_not_exist")).unwrap();
+        assert!(!fonts.is_empty());
+    }
+
+    /// Tests whether the data folder is found without relying on the working directory.
+    #[test]
+    fn data_dir_does_not_depend_on_working_directory() {
+        let data_dir = find_data_dir("babamodmanager tests").unwrap();
+        assert!(data_dir.is_absolute(), "{}", data_dir.display());
+        assert!(data_dir.join(FONTS_DIR).is_dir());
+        assert!(data_dir.join(PALETTES_DIR).is_dir());
+    }
+
+    /// Tests whether or not the bundled palette is loaded when the palettes folder doesn't exist
+    #[test]
+    fn bundled_palette_without_directory() {
+        let root = scratch_dir("bundled_palette_without_directory");
+        let themes = load_themes_from(&root.join("does_not_exist")).unwrap();
+        assert_eq!(themes.len(), 1);
+    }
+
+    /// Tests whether the options survive being saved and loaded
+    #[test]
+    fn app_options_round_trip() {
+        let options = AppOptions {
+            theme: ThemeData::default(),
+            light_mode: true,
+            font: "LibSans".to_owned(),
+            sort_order: PackSortOrder::Collectibles,
+        };
+        let serialized = serde_json::to_string(&options).unwrap();
+        let deserialized: AppOptions = serde_json::from_str(&serialized).unwrap();
+        assert!(deserialized.light_mode);
+        assert_eq!(deserialized.font, "LibSans");
+        assert_eq!(deserialized.sort_order, PackSortOrder::Collectibles);
+        assert_eq!(serialized, serde_json::to_string(&deserialized).unwrap());
+    }
+
+    /// Tests whether the levelpacks listed on the overview stay as they were until they're refreshed
+    #[test]
+    fn packs_are_only_read_on_refresh() {
+        let root = scratch_dir("packs_are_only_read_on_refresh");
+        let pack = fixture_pack(
+            &root.join("Data").join("Worlds"),
+            "pack",
+            "[general]\nname=Pack\nmods=1\n",
+        );
+        fixture_mod(&pack.join("Lua"), "first", "");
+        let mut state = AppState::default();
+        state.set_files(BabaFiles::from_raw(root));
+        state.refresh_packs().unwrap();
+        fixture_mod(&pack.join("Lua"), "second", "");
+        let mod_count = |state: &AppState| {
+            state.packs.as_ref().unwrap()[0]
+                .mods
+                .as_ref()
+                .unwrap()
+                .len()
+        };
+        assert_eq!(mod_count(&state), 1);
+        state.refresh_packs().unwrap();
+        assert_eq!(mod_count(&state), 2);
+    }
+
+    /// Tests whether a dropped mod's conflicts are found among the cached mods of a listed levelpack
+    #[test]
+    fn listed_pack_conflicts_with_dropped_mod() {
+        let root = scratch_dir("listed_pack_conflicts_with_dropped_mod");
+        let pack = fixture_pack(&root, "pack", "[general]\nname=Pack\nmods=1\n");
+        fixture_mod(&pack.join("Lua"), "clashing", "function init()\nend\n");
+        fixture_mod(&pack.join("Lua"), "peaceful", "function other()\nend\n");
+        let dropped = root.join("dropped.lua");
+        fs::write(&dropped, "function init()\nend\n").unwrap();
+        let listed = ListedPack::from(LevelpackRepr::new(pack).unwrap());
+        assert_eq!(
+            listed.conflicts_with(&BabaMod::new(dropped)),
+            vec!["clashing".to_owned()]
+        );
+    }
+
+    /// Tests whether only the most recent error is kept until it's dismissed
+    #[test]
+    fn pushing_error_sets_last_error() {
+        let mut state = AppState::default();
+        assert!(state.last_error.is_none());
+        state.push_error(BabaError::Levelpack(LevelpackError::LevelpackDoesNotExist(
+            PathBuf::from("nowhere"),
+        )));
+        state.push_error(BabaError::Levelpack(LevelpackError::LevelpackDoesNotExist(
+            PathBuf::from("nowhere"),
+        )));
+        assert!(state.last_error.as_ref().unwrap().contains("nowhere"));
+        state.dismiss_error();
+        assert!(state.last_error.is_none());
+    }
+
+    /// Tests whether a theme is shown as one hex code per color
+    #[test]
+    fn theme_displays_as_hex() {
+        let theme = ThemeData::default();
+        let text = theme.to_string();
+        assert_eq!(text.lines().count(), 13);
+        assert_eq!(text.lines().next(), Some("dark: #2d2922"));
+        assert_eq!(text.lines().last(), Some("bonus: #d8396a"));
+        assert_eq!(theme.to_hex_map()["link"], "#82c7e4");
+    }
+
+    /// Tests whether a theme can be read back from hex codes, and rejects the wrong amount of colors
+    #[test]
+    fn theme_from_hex_lines() {
+        let theme = ThemeData::default();
+        let codes: Vec<String> = theme.to_hex_map().into_values().collect();
+        let lines = codes.join("\n");
+        assert!(ThemeData::from_hex_lines(&lines).is_ok());
+        assert!(matches!(
+            ThemeData::from_hex_lines(&codes[..12].join("\n")),
+            Err(ApplicationError::ThemeColorCount(12))
+        ));
+        let malformed = lines.replacen('#', "not a color ", 1);
+        assert!(matches!(
+            ThemeData::from_hex_lines(&malformed),
+            Err(ApplicationError::ColorParsing(_))
+        ));
+
+        let ordered: Vec<String> = theme
+            .to_string()
+            .lines()
+            .map(|line| line.split_once(": ").unwrap().1.to_owned())
+            .collect();
+        let parsed = ThemeData::from_hex_lines(&ordered.join("\n")).unwrap();
+        assert_eq!(parsed.colors(), theme.colors());
+    }
+
+    /// Tests whether the palettes are only loaded the first time they're needed
+    #[test]
+    fn palettes_are_only_loaded_once() {
+        let mut state = AppState::default();
+        let mut loads = 0;
+        for _ in 0..3 {
+            state
+                .ensure_palettes(|| {
+                    loads += 1;
+                    Ok(vec![ThemeData::default()])
+                })
+                .unwrap();
+        }
+        assert_eq!(loads, 1);
+        assert_eq!(state.palettes.len(), 1);
+    }
+
+    /// Tests whether a mod's icon is loaded from a local path
+    #[test]
+    fn mod_icon_from_local_path() {
+        let root = scratch_dir("mod_icon_from_local_path");
+        let mod_path = root.join("pictured");
+        fs::create_dir_all(&mod_path).unwrap();
+        let icon = mod_path.join("icon.png");
+        ThemeData::default().save_palette(&icon).unwrap();
+        let json = config_with(
+            "pictured",
+            &[],
+            serde_json::json!({ "icon_url": icon.display().to_string() }),
+        );
+        fs::write(mod_path.join("Config.json"), json.to_string()).unwrap();
+        let cache = root.join("cache");
+
+        let baba_mod = BabaMod::new(mod_path);
+        let image = baba_mod.icon(&cache).unwrap().unwrap();
+        assert_eq!(image.size, [7, 5]);
+        assert!(baba_mod.banner(&cache).unwrap().is_none());
+        assert!(BabaMod::new(root.join("no_config"))
+            .icon(&cache)
+            .unwrap()
+            .is_none());
+    }
+
+    /// Tests whether the installation is remembered once found, including between runs
+    #[test]
+    fn app_state_knows_when_files_are_found() {
+        let mut state = AppState::default();
+        assert!(!state.has_files());
+        state.set_files(BabaFiles::from_raw(scratch_dir(
+            "app_state_knows_when_files_are_found",
+        )));
+        assert!(state.has_files());
+        // the installation is remembered between runs
+        let saved = serde_json::to_string(&state).unwrap();
+        assert!(serde_json::from_str::<AppState>(&saved)
+            .unwrap()
+            .has_files());
+    }
+
+    /// Tests whether dropped mods are queued to be installed, and anything else is rejected
+    #[test]
+    fn dropped_files_are_queued_or_rejected() {
+        let root = scratch_dir("dropped_files_are_queued_or_rejected");
+        let folder = fixture_mod(&root, "folder_mod", "function a()\nend\n");
+        let single = root.join("single.lua");
+        fs::write(&single, "function b()\nend\n").unwrap();
+        let readme = root.join("readme.txt");
+        fs::write(&readme, "not a mod").unwrap();
+        let dropped = |path: &PathBuf| egui::DroppedFile {
+            path: Some(path.clone()),
+            ..Default::default()
+        };
+
+        let mut state = AppState::default();
+        state.receive_dropped_files(&[dropped(&folder), dropped(&single)]);
+        let queued: Vec<_> = state.dropped_mods.iter().map(|m| m.path()).collect();
+        assert_eq!(queued, [folder.as_path(), single.as_path()]);
+        assert!(state.last_error.is_none());
+
+        state.receive_dropped_files(&[dropped(&readme)]);
+        assert_eq!(state.dropped_mods.len(), 2);
+        assert!(state.last_error.is_some());
+        assert!(matches!(
+            mod_from_dropped_file(&dropped(&readme)),
+            Err(BabaError::Application(ApplicationError::NotAMod(_)))
+        ));
+        // files dropped without a path (as on the web) can't be installed
+        assert!(mod_from_dropped_file(&egui::DroppedFile::default()).is_err());
+    }
+
+    /// Tests whether the overview's filter matches the details of packs and their mods
+    #[test]
+    fn filter_matches_pack_and_mod_details() {
+        let root = scratch_dir("filter_matches_pack_and_mod_details");
+        let pack_path = fixture_pack(
+            &root,
+            "pack",
+            "[general]\nname=Lost Island\nauthor=Hempuli\nmods=1\n",
+        );
+        fixture_mod_with(
+            &pack_path.join("Lua"),
+            "weather",
+            "function rain()\nend\n",
+            serde_json::json!({ "tags": ["Visuals"] }),
+        );
+
+        let pack = LevelpackRepr::new(pack_path).unwrap();
+        let mods = pack.mods().unwrap();
+        for query in ["", "lost", "ISLAND", "hemp", "weath", "visual"] {
+            assert!(
+                matches_filter(&pack, &mods, query),
+                "{:?} should match",
+                query
+            );
+        }
+        for query in ["sunny", "rain"] {
+            assert!(
+                !matches_filter(&pack, &mods, query),
+                "{:?} shouldn't match",
+                query
+            );
+        }
+        // only the given mods are searched, not the ones on disk
+        assert!(!matches_filter(&pack, &[], "weath"));
+    }
+
+    /// Tests whether levelpacks are sorted by each sort order
+    #[test]
+    fn packs_sort_by_the_chosen_order() {
+        let root = scratch_dir("packs_sort_by_the_chosen_order");
+        let packs: Vec<LevelpackRepr> = [
+            (
+                "b",
+                "name=beta\nauthor=Zed\nprize_max=5\nclear_max=0\nbonus_max=0",
+            ),
+            (
+                "a",
+                "name=Alpha\nauthor=amy\nprize_max=1\nclear_max=1\nbonus_max=1",
+            ),
+            (
+                "c",
+                "name=Gamma\nauthor=Amy\nprize_max=2\nclear_max=1\nbonus_max=1",
+            ),
+        ]
+        .iter()
+        .map(|(folder, fields)| {
+            let world_data = format!("[general]\n{}\n", fields);
+            LevelpackRepr::new(fixture_pack(&root, folder, &world_data)).unwrap()
+        })
+        .collect();
+        let sorted_names = |order: PackSortOrder| {
+            let mut packs: Vec<&LevelpackRepr> = packs.iter().collect();
+            packs.sort_by(|left, right| compare_packs(left, right, order));
+            packs.iter().map(|pack| pack.name()).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            sorted_names(PackSortOrder::Name),
+            ["Alpha", "beta", "Gamma"]
+        );
+        // "amy" and "Amy" are the same author, so they keep their original order
+        assert_eq!(
+            sorted_names(PackSortOrder::Author),
+            ["Alpha", "Gamma", "beta"]
+        );
+        assert_eq!(
+            sorted_names(PackSortOrder::Collectibles),
+            ["Alpha", "Gamma", "beta"]
+        );
+    }
+
+    /// Tests whether a font that can't be found is named in the error
+    #[test]
+    fn missing_font_is_named_in_the_error() {
+        let fonts = load_fonts().unwrap();
+        let options = AppOptions {
+            font: "Missing.ttf".to_owned(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            options.selected_font_with_size(12.0, &fonts),
+            Err(BabaError::Application(ApplicationError::FontUnavailable(ref font))) if font == "Missing.ttf"
+        ));
+        let options = AppOptions {
+            font: DEFAULT_FONT.to_owned(),
+            ..Default::default()
+        };
+        assert_eq!(
+            options.selected_font_with_size(12.0, &fonts).unwrap().size,
+            12.0
+        );
+    }
+
+    /// Tests whether every loaded font has its own family, and falls back into the proportional family
+    #[test]
+    fn loaded_fonts_belong_to_families() {
+        let root = scratch_dir("loaded_fonts_belong_to_families");
+        fs::write(root.join("Custom.ttf"), "").unwrap();
+        for font in load_fonts_from(&root).unwrap() {
+            let families: Vec<_> = font.families.iter().map(|f| f.family.clone()).collect();
+            assert!(!families.is_empty(), "{} has no families", font.name);
+            assert!(families.contains(&egui::FontFamily::Proportional));
+            assert!(families.contains(&egui::FontFamily::Name(font.name.as_str().into())));
+        }
+    }
+}